@@ -1,5 +1,5 @@
-use aori_types::constants::*;
+use aori_types::chains::ChainConfig;
 
 fn main() {
-    println!("{}", CURRENT_SEAPORT_ADDRESS);
+    println!("{}", ChainConfig::default().seaport_address);
 }
@@ -1,6 +1,7 @@
 use crate::types::Executor;
 use anyhow::Result;
 use aori_requests::aori_provider::AoriProvider;
+use aori_types::matching::OrderMatch;
 use async_trait::async_trait;
 use std::sync::Arc;
 use tokio::sync::Mutex;
@@ -38,3 +39,36 @@ impl Executor<serde_json::Value> for AoriExecutor {
         }
     }
 }
+
+/// An executor that settles matched orders on-chain through the Aori Provider's wallet.
+pub struct MatchExecutor {
+    aori_provider: Arc<Mutex<AoriProvider>>,
+}
+
+impl MatchExecutor {
+    pub fn new(aori_provider: Arc<Mutex<AoriProvider>>) -> Self {
+        Self { aori_provider }
+    }
+}
+
+#[async_trait]
+impl Executor<OrderMatch> for MatchExecutor {
+    /// Send a `matchOrders` transaction and wait for it to be mined.
+    async fn execute(&mut self, order_match: OrderMatch) -> Result<()> {
+        info!("Matching {} orders", order_match.orders.len());
+        let result = {
+            let provider = self.aori_provider.lock().await;
+            provider.match_orders_onchain(&order_match).await
+        };
+        match result {
+            Ok(receipt) => {
+                info!("Orders matched in {:?}", receipt.transaction_hash);
+                Ok(())
+            }
+            Err(e) => {
+                error!("Match error: {}", e);
+                Err(anyhow::anyhow!("Match error: {}", e))
+            }
+        }
+    }
+}
@@ -1,13 +1,24 @@
+use crate::counters::CounterCache;
+use crate::onchain;
 use crate::requests::*;
+use alloy_primitives::{Address, B256, U256};
 use aori_types::{
-    constants::{MARKET_FEED_URL, REQUEST_URL},
+    chains::{ChainConfig, ChainRegistry},
+    events::OrderCreatedData,
+    fill::FillAmount,
+    fillability::FillabilityVerdict,
+    market::{Pair, Side},
+    matching::OrderMatch,
+    responses::AoriOrderStatusData,
     seaport::OrderComponents,
+    status::StatusMismatch,
 };
 use ethers::{
+    middleware::SignerMiddleware,
     prelude::{LocalWallet, Ws},
     providers::{Middleware, Provider},
     signers::Signer,
-    types::Signature,
+    types::{Signature, TransactionReceipt},
 };
 use eyre::Context;
 use serde_json::Value;
@@ -27,15 +38,18 @@ pub struct AoriProvider {
     pub feed_conn: WebSocketStream<MaybeTlsStream<TcpStream>>,
     pub wallet: Option<LocalWallet>,
     pub chain_id: Option<u64>,
+    pub chains: ChainRegistry,
+    pub counters: CounterCache,
     pub last_id: Arc<Mutex<u64>>,
     pub wallet_addr: Option<String>,
     pub wallet_sig: Option<String>,
+    pub node: Option<Provider<Ws>>,
 }
 async fn initialize_wallet(
     key: &str,
     address: &str,
     node: String,
-) -> Result<(LocalWallet, u64, String, String)> {
+) -> Result<(LocalWallet, u64, String, String, Provider<Ws>)> {
     let pv = Provider::<Ws>::connect(&node).await?;
     let chain_id = pv.get_chainid().await?.low_u64();
     let wallet = key.parse::<LocalWallet>()?.with_chain_id(chain_id);
@@ -43,15 +57,30 @@ async fn initialize_wallet(
     let wallet_addr = address.to_string();
     let wallet_sig = format!("0x{}", sig).to_string();
 
-    Ok((wallet, chain_id, wallet_addr, wallet_sig))
+    Ok((wallet, chain_id, wallet_addr, wallet_sig, pv))
 }
-async fn connect_websockets() -> anyhow::Result<(
+// the built-in chains, with any overrides from the file at CHAIN_CONFIG
+fn load_chains() -> Result<ChainRegistry> {
+    let chains = ChainRegistry::presets();
+    match std::env::var("CHAIN_CONFIG") {
+        Ok(path) => Ok(chains
+            .load_overrides(&path)
+            .map_err(|err| anyhow::anyhow!("{}: {}", path, err))?),
+        Err(_) => Ok(chains),
+    }
+}
+
+async fn connect_websockets(
+    chain: &ChainConfig,
+) -> anyhow::Result<(
     WebSocketStream<MaybeTlsStream<TcpStream>>,
     WebSocketStream<MaybeTlsStream<TcpStream>>,
 )> {
-    let ((request_conn, _), (feed_conn, _)) =
-        tokio::try_join!(connect_async(REQUEST_URL), connect_async(MARKET_FEED_URL))
-            .expect("Failed to connect to websockets");
+    let ((request_conn, _), (feed_conn, _)) = tokio::try_join!(
+        connect_async(chain.request_url.as_str()),
+        connect_async(chain.feed_url.as_str())
+    )
+    .expect("Failed to connect to websockets");
 
     Ok((request_conn, feed_conn))
 }
@@ -68,30 +97,46 @@ impl AoriProvider {
             .context("missing NODE_URL")
             .unwrap();
 
-        let (request_conn, feed_conn) = connect_websockets().await?;
-        let (wallet, chain_id, wallet_addr, wallet_sig) =
+        let chains = load_chains()?;
+        let (wallet, chain_id, wallet_addr, wallet_sig, node) =
             initialize_wallet(&key, &address, node).await?;
+        let chain = chains
+            .chain(chain_id)
+            .map_err(|err| anyhow::anyhow!("{}", err))?;
+        let (request_conn, feed_conn) = connect_websockets(chain).await?;
 
         Ok(Self {
             request_conn,
             feed_conn,
             wallet: Some(wallet),
             chain_id: Some(chain_id),
+            chains,
+            counters: CounterCache::new(),
             last_id: Arc::new(Mutex::new(0)),
             wallet_addr: Some(wallet_addr),
             wallet_sig: Some(wallet_sig),
+            node: Some(node),
         })
     }
     pub async fn new_vanilla() -> Result<Self, anyhow::Error> {
-        let (request_conn, feed_conn) = connect_websockets().await?;
+        let chains = load_chains()?;
+        let (request_conn, feed_conn) = connect_websockets(
+            chains
+                .default_chain()
+                .map_err(|err| anyhow::anyhow!("{}", err))?,
+        )
+        .await?;
         Ok(Self {
             request_conn,
             feed_conn,
             wallet: None,
             chain_id: None,
+            chains,
+            counters: CounterCache::new(),
             last_id: Arc::new(Mutex::new(0)),
             wallet_addr: None,
             wallet_sig: None,
+            node: None,
         })
     }
 
@@ -144,43 +189,47 @@ impl AoriProvider {
 
     pub async fn view_orderbook(
         &mut self,
-        base: &str,
-        quote: &str,
-        side: &str,
+        base: Address,
+        quote: Address,
+        side: Side,
     ) -> eyre::Result<()> {
         let chain_id = self.chain_id.ok_or(eyre::eyre!("Chain ID is not set"))?;
-        let view_orderbook_payload =
-            create_view_orderbook_payload(&self.last_id, chain_id, base, quote, side);
+        let pair = Pair::new(chain_id, base, quote);
+        let view_orderbook_payload = create_view_orderbook_payload(&self.last_id, &pair, side);
         self.request_conn
             .send(Message::Text(view_orderbook_payload.to_string()))
             .await?;
         Ok(())
     }
 
-    pub async fn make_order(&mut self, order_params: OrderComponents) -> eyre::Result<()> {
-        let wallet = self.wallet.as_ref().ok_or(eyre::eyre!(
-            "wallet: add wallet private key to auth wallet."
-        ))?;
-        let chain_id = self
-            .chain_id
-            .ok_or(eyre::eyre!("chain_id: Chain ID is not set."))?;
-        let signed_order_payload =
-            create_make_order_payload(&self.last_id, wallet, order_params, chain_id)?;
-        self.request_conn
-            .send(Message::Text(signed_order_payload.to_string()))
-            .await?;
-        Ok(())
+    /// Signs and places `order_params`, always at the wallet's current Seaport counter,
+    /// whatever counter it was built with.
+    pub async fn make_order(
+        &mut self,
+        order_params: OrderComponents,
+        options: &MakeOrderOptions,
+    ) -> eyre::Result<()> {
+        let chain_id = self.chain()?.chain_id;
+        self.make_order_with_chain_id(order_params, chain_id, options)
+            .await
     }
+
+    /// Like `make_order`, for any configured chain. The counter comes from the connected
+    /// node, or from the cache for other chains; if neither has it, the order isn't signed,
+    /// since a wrong counter makes the signature invalid.
     pub async fn make_order_with_chain_id(
         &mut self,
-        order_params: OrderComponents,
+        mut order_params: OrderComponents,
         chain_id: u64,
+        options: &MakeOrderOptions,
     ) -> eyre::Result<()> {
+        order_params.counter = self.counter(chain_id, order_params.offerer).await?;
         let wallet = self.wallet.as_ref().ok_or(eyre::eyre!(
             "wallet: add wallet private key to auth wallet."
         ))?;
+        let chain = self.chains.chain(chain_id)?;
         let signed_order_payload =
-            create_make_order_payload(&self.last_id, wallet, order_params, chain_id)?;
+            create_make_order_payload(&self.last_id, wallet, order_params, chain, options)?;
         self.request_conn
             .send(Message::Text(signed_order_payload.to_string()))
             .await?;
@@ -193,6 +242,7 @@ impl AoriProvider {
         order_id: &str,
         seat_id: &str,
         api_key: &str,
+        fill: &FillAmount,
     ) -> eyre::Result<()> {
         let wallet = self.wallet.as_ref().ok_or(eyre::eyre!(
             "wallet: add wallet private key to auth wallet."
@@ -201,9 +251,11 @@ impl AoriProvider {
             &self.last_id,
             wallet,
             order_params,
+            self.chain()?,
             order_id,
             seat_id,
             api_key,
+            fill,
         )?;
         self.request_conn
             .send(Message::Text(take_order_payload.to_string()))
@@ -266,6 +318,177 @@ impl AoriProvider {
         Ok(())
     }
 
+    /// The configuration of the chain the wallet is connected to.
+    pub fn chain(&self) -> eyre::Result<&ChainConfig> {
+        let chain_id = self
+            .chain_id
+            .ok_or(eyre::eyre!("chain_id: Chain ID is not set."))?;
+        self.chains.chain(chain_id)
+    }
+
+    ////////////////// ON-CHAIN //////////////////
+
+    fn signer_client(&self) -> eyre::Result<SignerMiddleware<Provider<Ws>, LocalWallet>> {
+        let wallet = self.wallet.as_ref().ok_or(eyre::eyre!(
+            "wallet: add wallet private key to send transactions."
+        ))?;
+        let node = self
+            .node
+            .as_ref()
+            .ok_or(eyre::eyre!("node: add NODE_URL to send transactions."))?;
+        Ok(SignerMiddleware::new(node.clone(), wallet.clone()))
+    }
+
+    /// Cancels orders on Seaport directly, rather than only asking the Aori server to drop them.
+    pub async fn cancel_orders_onchain(
+        &self,
+        orders: Vec<OrderComponents>,
+    ) -> eyre::Result<TransactionReceipt> {
+        onchain::cancel_orders(&self.signer_client()?, self.chain()?, orders).await
+    }
+
+    /// Takes `fill` of a signed order on Seaport directly, paying for it from this wallet
+    /// through `fulfiller_conduit_key`, e.g. `ChainConfig::default_conduit_key`. `extra_data`
+    /// is passed on to the order's zone.
+    pub async fn fulfill_order_onchain(
+        &self,
+        order: OrderComponents,
+        signature: Vec<u8>,
+        fill: &FillAmount,
+        extra_data: Vec<u8>,
+        fulfiller_conduit_key: B256,
+    ) -> eyre::Result<TransactionReceipt> {
+        onchain::fulfill_order(
+            &self.signer_client()?,
+            self.chain()?,
+            order,
+            signature,
+            fill,
+            extra_data,
+            fulfiller_conduit_key,
+        )
+        .await
+    }
+
+    /// Settles matched orders against each other on Seaport, keeping the surplus.
+    pub async fn match_orders_onchain(
+        &self,
+        order_match: &OrderMatch,
+    ) -> eyre::Result<TransactionReceipt> {
+        onchain::match_orders(&self.signer_client()?, self.chain()?, order_match).await
+    }
+
+    /// How much of `token` this wallet has approved for orders using `conduit_key`.
+    pub async fn allowance_onchain(&self, token: Address, conduit_key: B256) -> eyre::Result<U256> {
+        let client = self.signer_client()?;
+        let owner = Address::from_slice(client.address().as_bytes());
+        onchain::allowance(Arc::new(client), self.chain()?, token, owner, conduit_key).await
+    }
+
+    /// Approves `amount` of `token` from this wallet to the spender behind `conduit_key`:
+    /// Seaport for the zero key, otherwise the conduit.
+    pub async fn approve_onchain(
+        &self,
+        token: Address,
+        conduit_key: B256,
+        amount: U256,
+    ) -> eyre::Result<TransactionReceipt> {
+        onchain::approve(
+            Arc::new(self.signer_client()?),
+            self.chain()?,
+            token,
+            conduit_key,
+            amount,
+        )
+        .await
+    }
+
+    /// Invalidates every order this wallet has signed so far by bumping its Seaport counter.
+    pub async fn increment_counter_onchain(&mut self) -> eyre::Result<TransactionReceipt> {
+        let chain = self.chain()?.clone();
+        let receipt = onchain::increment_counter(&self.signer_client()?, &chain).await?;
+        self.counters.apply_logs(&chain, &receipt.logs)?;
+        Ok(receipt)
+    }
+
+    /// The Seaport counter `offerer`'s orders on `chain_id` must be signed at, cached after
+    /// the first lookup. Reads go through the connected node, which must be on `chain_id`.
+    pub async fn counter(&mut self, chain_id: u64, offerer: Address) -> eyre::Result<U256> {
+        if self.chain_id != Some(chain_id) {
+            if let Some(counter) = self.counters.get(chain_id, offerer) {
+                return Ok(counter);
+            }
+            return Err(eyre::eyre!(
+                "counter: the node is not connected to chain {}.",
+                chain_id
+            ));
+        }
+        let node = self
+            .node
+            .as_ref()
+            .ok_or(eyre::eyre!("node: add NODE_URL to read counters."))?;
+        let chain = self.chains.chain(chain_id)?;
+        self.counters.counter(node, chain, offerer).await
+    }
+
+    /// Checks an `aori_orderStatus` reply against Seaport. Orders the server shows as active
+    /// but that are cancelled or (partly) filled on-chain come back as a mismatch.
+    pub async fn cross_check_order_status(
+        &self,
+        status: &AoriOrderStatusData,
+    ) -> eyre::Result<Option<StatusMismatch>> {
+        let node = self
+            .node
+            .as_ref()
+            .ok_or(eyre::eyre!("node: add NODE_URL to read order statuses."))?;
+        let onchain = onchain::get_order_status(node, self.chain()?, status.order_hash).await?;
+        Ok(status.cross_check(&onchain))
+    }
+
+    /// Whether each `(order, fill)` on the connected chain could be taken right now: that the
+    /// order is within its time window and signed at the current counter, and that its
+    /// offerer holds and has approved what the fill pays out. Balances and allowances are read
+    /// in one multicall.
+    pub async fn check_fillability(
+        &mut self,
+        orders: &[(OrderComponents, FillAmount)],
+    ) -> eyre::Result<Vec<FillabilityVerdict>> {
+        let chain_id = self.chain()?.chain_id;
+        let node = self
+            .node
+            .clone()
+            .ok_or(eyre::eyre!("node: add NODE_URL to check orders."))?;
+        onchain::check_fillability(
+            Arc::new(node),
+            self.chains.chain(chain_id)?,
+            orders,
+            &mut self.counters,
+        )
+        .await
+    }
+
+    /// Like `cross_check_order_status`, for orders from the orderbook or the feed.
+    pub async fn cross_check_order(
+        &self,
+        order: &OrderCreatedData,
+    ) -> eyre::Result<Option<StatusMismatch>> {
+        let node = self
+            .node
+            .as_ref()
+            .ok_or(eyre::eyre!("node: add NODE_URL to read order statuses."))?;
+        let chain = self.chains.chain(order.chain_id as u64)?;
+        onchain::cross_check_order_status(node, chain, order.order_hash, order.active).await
+    }
+
+    /// Whether the order's hash matches its parameters signed at the offerer's current
+    /// counter. Orders signed before the offerer bumped its counter fail this check.
+    pub async fn verify_order_hash(&mut self, order: &OrderCreatedData) -> eyre::Result<bool> {
+        let counter = self
+            .counter(order.chain_id as u64, order.order.parameters.offerer)
+            .await?;
+        Ok(order.verify_order_hash(counter))
+    }
+
     // pub async fn cancel_all_orders(&mut self, api_key: &str) -> eyre::Result<()> {
     //     let cancel_all_order_payload = create_cancel_all_payload(&self.last_id, &self.wallet_addr, &self.wallet_sig, api_key);
     //     self.request_conn.send_text(cancel_all_order_payload.to_string()).await?;
@@ -292,10 +515,10 @@ mod tests {
     use super::*;
     use alloy_primitives::{address, Address, U256};
     use alloy_sol_types::SolStruct;
-    use aori_types::constants::{DEFAULT_CONDUIT_KEY, DEFAULT_ORDER_ADDRESS, DEFAULT_ZONE_HASH};
-    use aori_types::seaport::{
-        ConsiderationItem, ItemType, OfferItem, OrderComponents, OrderType, SEAPORT_DOMAIN,
-    };
+    use aori_types::constants::{DEFAULT_CONDUIT_KEY, DEFAULT_ZONE_HASH};
+    use aori_types::order_builder::OrderBuilder;
+    use aori_types::responses::{parse_response, AoriAuthData, AoriOrderHashData};
+    use aori_types::seaport::{ConsiderationItem, ItemType, OfferItem, OrderComponents, OrderType};
     use ethers::types::H256;
 
     use futures::StreamExt;
@@ -323,7 +546,7 @@ mod tests {
         };
         let order_components = OrderComponents {
             offerer: Address::ZERO,
-            zone: DEFAULT_ORDER_ADDRESS,
+            zone: ChainConfig::goerli().zone,
             offer: vec![offer_item],
             consideration: vec![consider_item],
             orderType: OrderType::PARTIAL_RESTRICTED as u8,
@@ -335,7 +558,7 @@ mod tests {
             counter: U256::from(0),
         };
 
-        let params_sig = order_components.eip712_signing_hash(&*SEAPORT_DOMAIN);
+        let params_sig = order_components.eip712_signing_hash(&apv.chain().unwrap().domain());
 
         /*
         https://docs.rs/ethers/latest/ethers/signers/struct.Wallet.html#method.sign_typed_data
@@ -400,19 +623,19 @@ mod tests {
         let response = apv.request_conn.next().await.unwrap().unwrap();
         // let binding = ws_stream.next().await.unwrap().unwrap();
         let response = response.to_text().unwrap();
-        let v: serde_json::Value = serde_json::from_str(response).unwrap();
-        let auth = v["result"]["auth"].as_str().unwrap().to_string();
+        let auth = parse_response::<AoriAuthData>(response)
+            .unwrap()
+            .result
+            .auth;
         println!("{:?}", &auth);
 
         apv.check_auth(&auth).await.unwrap();
         // Reading a response
         let binding = apv.request_conn.next().await.unwrap().unwrap();
         let response = binding.to_text().unwrap();
-        let authed = serde_json::from_str::<serde_json::Value>(response).unwrap()["result"]
-            .as_bool()
-            .unwrap();
+        let authed = parse_response::<bool>(response).unwrap().result;
         println!("{}", authed);
-        assert_eq!(authed, true);
+        assert!(authed);
     }
 
     #[tokio::test]
@@ -421,47 +644,31 @@ mod tests {
         let wallet = std::env::var("WALLET_ADDRESS")
             .context("missing WALLET_ADDRESS")
             .unwrap();
-        let start_time = chrono::Utc::now().timestamp_millis();
-        let end_time = chrono::Utc::now().timestamp_millis() + 1000 * 60 * 60 * 24;
         let mut apv = AoriProvider::new_from_env()
             .await
             .expect("Failed to create Aori Provider");
-        let offer_item = OfferItem {
-            itemType: ItemType::ERC20 as u8,
-            token: address!("2715Ccea428F8c7694f7e78B2C89cb454c5F7294"),
-            identifierOrCriteria: U256::from(0),
-            startAmount: U256::from(1000000000000000_u128),
-            endAmount: U256::from(1000000000000000_u128),
-        };
-        let consider_item = ConsiderationItem {
-            itemType: ItemType::ERC20 as u8,
-            token: address!("D3664B5e72B46eaba722aB6f43c22dBF40181954"),
-            identifierOrCriteria: U256::from(0),
-            startAmount: U256::from(1500000),
-            endAmount: U256::from(1500000),
-            recipient: Address::parse_checksummed(&wallet, None).unwrap(),
-        };
-        let order_params = OrderComponents {
-            offerer: Address::parse_checksummed(&wallet, None).unwrap(),
-            zone: DEFAULT_ORDER_ADDRESS,
-            offer: vec![offer_item.clone()],
-            consideration: vec![consider_item.clone()],
-            orderType: OrderType::PARTIAL_RESTRICTED as u8,
-            startTime: U256::from(start_time),
-            endTime: U256::from(end_time),
-            zoneHash: DEFAULT_ZONE_HASH.into(),
-            salt: U256::from(0),
-            conduitKey: DEFAULT_CONDUIT_KEY.into(),
-            // totalOriginalConsiderationItems: U256::from(1),
-            counter: U256::from(0),
-        };
+        let order_params = OrderBuilder::new(Address::parse_checksummed(&wallet, None).unwrap())
+            .offer_erc20(
+                address!("2715Ccea428F8c7694f7e78B2C89cb454c5F7294"),
+                U256::from(1000000000000000_u128),
+            )
+            .consideration_erc20(
+                address!("D3664B5e72B46eaba722aB6f43c22dBF40181954"),
+                U256::from(1500000),
+            )
+            .build()
+            .unwrap();
 
-        apv.make_order(order_params).await.unwrap();
+        apv.make_order(order_params, &MakeOrderOptions::default())
+            .await
+            .unwrap();
 
         let binding = apv.request_conn.next().await.unwrap().unwrap();
         let response = binding.to_text().unwrap();
-        let v: serde_json::Value = serde_json::from_str(response).unwrap();
-        let hash = v["result"]["orderHash"].as_str().unwrap().to_string();
+        let hash = parse_response::<AoriOrderHashData>(response)
+            .unwrap()
+            .result
+            .order_hash;
         println!("{:?}", hash);
     }
     #[tokio::test]
@@ -470,47 +677,31 @@ mod tests {
         let wallet = std::env::var("WALLET_ADDRESS")
             .context("missing WALLET_ADDRESS")
             .unwrap();
-        let start_time = chrono::Utc::now().timestamp_millis();
-        let end_time = chrono::Utc::now().timestamp_millis() + 1000 * 60 * 60 * 24;
         let mut apv = AoriProvider::new_from_env()
             .await
             .expect("Failed to create Aori Provider");
-        let offer_item = OfferItem {
-            itemType: ItemType::ERC20 as u8,
-            token: address!("2715Ccea428F8c7694f7e78B2C89cb454c5F7294"),
-            identifierOrCriteria: U256::from(0),
-            startAmount: U256::from(1000000000000000_u128),
-            endAmount: U256::from(1000000000000000_u128),
-        };
-        let consider_item = ConsiderationItem {
-            itemType: ItemType::ERC20 as u8,
-            token: address!("D3664B5e72B46eaba722aB6f43c22dBF40181954"),
-            identifierOrCriteria: U256::from(0),
-            startAmount: U256::from(1500000),
-            endAmount: U256::from(1500000),
-            recipient: Address::parse_checksummed(&wallet, None).unwrap(),
-        };
-        let order_params = OrderComponents {
-            offerer: Address::parse_checksummed(&wallet, None).unwrap(),
-            zone: DEFAULT_ORDER_ADDRESS,
-            offer: vec![offer_item.clone()],
-            consideration: vec![consider_item.clone()],
-            orderType: OrderType::PARTIAL_RESTRICTED as u8,
-            startTime: U256::from(start_time),
-            endTime: U256::from(end_time),
-            zoneHash: DEFAULT_ZONE_HASH.into(),
-            salt: U256::from(0),
-            conduitKey: DEFAULT_CONDUIT_KEY.into(),
-            // totalOriginalConsiderationItems: U256::from(1),
-            counter: U256::from(0),
-        };
+        let order_params = OrderBuilder::new(Address::parse_checksummed(&wallet, None).unwrap())
+            .offer_erc20(
+                address!("2715Ccea428F8c7694f7e78B2C89cb454c5F7294"),
+                U256::from(1000000000000000_u128),
+            )
+            .consideration_erc20(
+                address!("D3664B5e72B46eaba722aB6f43c22dBF40181954"),
+                U256::from(1500000),
+            )
+            .build()
+            .unwrap();
 
-        apv.make_order_with_chain_id(order_params, 5).await.unwrap();
+        apv.make_order_with_chain_id(order_params, 5, &MakeOrderOptions::default())
+            .await
+            .unwrap();
 
         let binding = apv.request_conn.next().await.unwrap().unwrap();
         let response = binding.to_text().unwrap();
-        let v: serde_json::Value = serde_json::from_str(response).unwrap();
-        let hash = v["result"]["orderHash"].as_str().unwrap().to_string();
+        let hash = parse_response::<AoriOrderHashData>(response)
+            .unwrap()
+            .result
+            .order_hash;
         println!("{:?}", hash);
     }
 }
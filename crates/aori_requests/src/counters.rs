@@ -0,0 +1,186 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use alloy_primitives::{Address, B256, U256};
+use aori_types::{
+    chains::ChainConfig,
+    events::OrderParameters,
+    seaport::{CounterIncremented, SeaportEvent},
+};
+use ethers::{providers::Middleware, types::Log};
+
+use crate::onchain;
+
+/// How long a cached counter is trusted without an event confirming it.
+pub const DEFAULT_MAX_AGE: Duration = Duration::from_secs(60);
+
+/// Seaport counters of offerers, per chain. A counter is read from Seaport the first time
+/// it is needed and then kept up to date from `CounterIncremented` events, so orders are
+/// signed and checked against the counter Seaport will actually use. Events can be missed,
+/// e.g. when nothing feeds them in, so counters older than `max_age` are read again.
+#[derive(Clone, Debug)]
+pub struct CounterCache {
+    counters: HashMap<(u64, Address), (U256, Instant)>,
+    max_age: Duration,
+}
+
+impl Default for CounterCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CounterCache {
+    pub fn new() -> Self {
+        Self {
+            counters: HashMap::new(),
+            max_age: DEFAULT_MAX_AGE,
+        }
+    }
+
+    /// Trusts cached counters for `max_age` instead of `DEFAULT_MAX_AGE`.
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = max_age;
+        self
+    }
+
+    /// The cached counter, unless it is older than `max_age`.
+    pub fn get(&self, chain_id: u64, offerer: Address) -> Option<U256> {
+        self.counters
+            .get(&(chain_id, offerer))
+            .filter(|(_, stored_at)| stored_at.elapsed() < self.max_age)
+            .map(|(counter, _)| *counter)
+    }
+
+    pub fn insert(&mut self, chain_id: u64, offerer: Address, counter: U256) {
+        self.counters
+            .insert((chain_id, offerer), (counter, Instant::now()));
+    }
+
+    /// Forgets the offerer's counter, so the next lookup reads it from Seaport again.
+    pub fn invalidate(&mut self, chain_id: u64, offerer: Address) {
+        self.counters.remove(&(chain_id, offerer));
+    }
+
+    /// The counter `offerer`'s orders on `chain` must be signed at, read through `client`
+    /// unless it is already cached.
+    pub async fn counter<M: Middleware + 'static>(
+        &mut self,
+        client: &M,
+        chain: &ChainConfig,
+        offerer: Address,
+    ) -> eyre::Result<U256> {
+        if let Some(counter) = self.get(chain.chain_id, offerer) {
+            return Ok(counter);
+        }
+        let counter = onchain::get_counter(client, chain, offerer).await?;
+        self.insert(chain.chain_id, offerer, counter);
+        Ok(counter)
+    }
+
+    /// Fills in the offerer's counter on `parameters` if the Aori API left it out.
+    pub async fn resolve<M: Middleware + 'static>(
+        &mut self,
+        client: &M,
+        chain: &ChainConfig,
+        parameters: OrderParameters,
+    ) -> eyre::Result<OrderParameters> {
+        match parameters.counter {
+            Some(_) => Ok(parameters),
+            None => {
+                let counter = self.counter(client, chain, parameters.offerer).await?;
+                Ok(parameters.with_counter(counter))
+            }
+        }
+    }
+
+    /// Takes the new counter out of a `CounterIncremented` event seen on `chain_id`. Other
+    /// events are ignored.
+    pub fn apply_event(&mut self, chain_id: u64, event: &SeaportEvent) {
+        if let SeaportEvent::CounterIncremented(CounterIncremented {
+            newCounter,
+            offerer,
+        }) = event
+        {
+            self.insert(chain_id, *offerer, *newCounter);
+        }
+    }
+
+    /// Applies every `CounterIncremented` event `chain`'s Seaport emitted among `logs`, e.g.
+    /// those of a transaction receipt or a log subscription.
+    pub fn apply_logs(&mut self, chain: &ChainConfig, logs: &[Log]) -> eyre::Result<()> {
+        for log in logs
+            .iter()
+            .filter(|log| log.address.as_bytes() == chain.seaport_address.as_slice())
+        {
+            let topics: Vec<B256> = log
+                .topics
+                .iter()
+                .map(|topic| B256::from_slice(topic.as_bytes()))
+                .collect();
+            if let Some(event) = SeaportEvent::decode(&topics, &log.data)? {
+                self.apply_event(chain.chain_id, &event);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+    use alloy_sol_types::SolEvent;
+    use ethers::types::{H160, H256};
+
+    const OFFERER: Address = address!("f39Fd6e51aad88F6F4ce6aB8827279cffFb92266");
+
+    fn counter_incremented_log(emitter: Address, new_counter: u64) -> Log {
+        let event = CounterIncremented {
+            newCounter: U256::from(new_counter),
+            offerer: OFFERER,
+        };
+        Log {
+            address: H160::from_slice(emitter.as_slice()),
+            topics: event
+                .encode_topics()
+                .iter()
+                .map(|topic| H256::from_slice(topic.0.as_slice()))
+                .collect(),
+            data: event.encode_data().into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn follows_counter_increments() {
+        let chain = ChainConfig::goerli();
+        let mut counters = CounterCache::new();
+        counters.insert(chain.chain_id, OFFERER, U256::ZERO);
+
+        counters
+            .apply_logs(
+                &chain,
+                &[
+                    counter_incremented_log(chain.seaport_address, 1),
+                    // not Seaport, so not trusted
+                    counter_incremented_log(chain.zone, 7),
+                ],
+            )
+            .unwrap();
+        assert_eq!(counters.get(chain.chain_id, OFFERER), Some(U256::from(1)));
+        assert_eq!(counters.get(1, OFFERER), None);
+
+        counters.invalidate(chain.chain_id, OFFERER);
+        assert_eq!(counters.get(chain.chain_id, OFFERER), None);
+    }
+
+    #[test]
+    fn counters_expire() {
+        let mut counters = CounterCache::new().with_max_age(Duration::ZERO);
+        counters.insert(5, OFFERER, U256::from(1));
+        assert_eq!(counters.get(5, OFFERER), None);
+    }
+}
@@ -1,2 +1,4 @@
 pub mod aori_provider;
+pub mod counters;
+pub mod onchain;
 pub mod requests;
@@ -0,0 +1,405 @@
+use crate::counters::CounterCache;
+use alloy_primitives::{Address as AlloyAddress, B256, U256 as AlloyU256};
+use aori_types::{
+    chains::ChainConfig,
+    fill::FillAmount,
+    fillability::{FillabilityVerdict, TokenFunds},
+    matching::OrderMatch,
+    seaport::{
+        decode_get_counter, decode_get_order_hash, decode_get_order_status, encode_cancel,
+        encode_fulfill_advanced_order, encode_get_counter, encode_get_order_hash,
+        encode_get_order_status, encode_increment_counter, getOrderStatusReturn, ItemType,
+        OrderComponents,
+    },
+    status::{cross_check, StatusMismatch},
+    ISeaport, IERC20,
+};
+use ethers::{
+    contract::Multicall,
+    providers::Middleware,
+    types::{
+        transaction::eip2718::TypedTransaction, Address, TransactionReceipt, TransactionRequest,
+        U256,
+    },
+};
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+// sends `calldata` (and `value` wei) to the chain's Seaport from the client's signer and waits
+// for it to be mined
+pub async fn send_seaport_transaction<M: Middleware + 'static>(
+    client: &M,
+    chain: &ChainConfig,
+    calldata: Vec<u8>,
+    value: AlloyU256,
+) -> eyre::Result<TransactionReceipt> {
+    let tx = TransactionRequest::new()
+        .to(Address::from_slice(chain.seaport_address.as_slice()))
+        .value(U256::from_big_endian(&value.to_be_bytes::<32>()))
+        .data(calldata);
+    let receipt = client
+        .send_transaction(tx, None)
+        .await?
+        .await?
+        .ok_or(eyre::eyre!("transaction dropped from the mempool."))?;
+
+    if receipt.status != Some(1.into()) {
+        return Err(eyre::eyre!(
+            "seaport transaction {:?} reverted.",
+            receipt.transaction_hash
+        ));
+    }
+    Ok(receipt)
+}
+
+// calls a view function on the chain's Seaport and returns the raw return data
+pub async fn call_seaport<M: Middleware + 'static>(
+    client: &M,
+    chain: &ChainConfig,
+    calldata: Vec<u8>,
+) -> eyre::Result<Vec<u8>> {
+    let tx: TypedTransaction = TransactionRequest::new()
+        .to(Address::from_slice(chain.seaport_address.as_slice()))
+        .data(calldata)
+        .into();
+    Ok(client.call(&tx, None).await?.to_vec())
+}
+
+/// The counter `offerer`'s orders must be signed at to be valid on Seaport.
+pub async fn get_counter<M: Middleware + 'static>(
+    client: &M,
+    chain: &ChainConfig,
+    offerer: AlloyAddress,
+) -> eyre::Result<AlloyU256> {
+    decode_get_counter(&call_seaport(client, chain, encode_get_counter(offerer)).await?)
+}
+
+/// The hash Seaport itself derives for `order`.
+pub async fn get_order_hash<M: Middleware + 'static>(
+    client: &M,
+    chain: &ChainConfig,
+    order: OrderComponents,
+) -> eyre::Result<B256> {
+    decode_get_order_hash(&call_seaport(client, chain, encode_get_order_hash(order)).await?)
+}
+
+/// Whether Seaport has seen the order validated or cancelled, and how much of it is filled.
+pub async fn get_order_status<M: Middleware + 'static>(
+    client: &M,
+    chain: &ChainConfig,
+    order_hash: B256,
+) -> eyre::Result<getOrderStatusReturn> {
+    decode_get_order_status(
+        &call_seaport(client, chain, encode_get_order_status(order_hash)).await?,
+    )
+}
+
+/// Checks an order against Seaport's own record of it. `active` is whether the Aori server
+/// still shows the order as open; if it does, but Seaport has it cancelled or (partly)
+/// filled, the mismatch is returned.
+pub async fn cross_check_order_status<M: Middleware + 'static>(
+    client: &M,
+    chain: &ChainConfig,
+    order_hash: B256,
+    active: bool,
+) -> eyre::Result<Option<StatusMismatch>> {
+    let status = get_order_status(client, chain, order_hash).await?;
+    Ok(cross_check(active, &status))
+}
+
+/// Cancels `orders` on the Seaport contract itself, so they can no longer be filled even if
+/// the Aori server still holds them. The client's signer must be the offerer of every order.
+pub async fn cancel_orders<M: Middleware + 'static>(
+    client: &M,
+    chain: &ChainConfig,
+    orders: Vec<OrderComponents>,
+) -> eyre::Result<TransactionReceipt> {
+    send_seaport_transaction(client, chain, encode_cancel(orders), AlloyU256::ZERO).await
+}
+
+/// Increments the signer's Seaport counter, invalidating every order it has signed so far.
+pub async fn increment_counter<M: Middleware + 'static>(
+    client: &M,
+    chain: &ChainConfig,
+) -> eyre::Result<TransactionReceipt> {
+    send_seaport_transaction(client, chain, encode_increment_counter(), AlloyU256::ZERO).await
+}
+
+/// Takes `fill` of a signed order directly on Seaport, handing `extra_data` to the order's
+/// zone. The signer pays through `fulfiller_conduit_key`, so its tokens must be approved to
+/// that key's approval target. Any ETH the order asks for is sent along with the call.
+pub async fn fulfill_order<M: Middleware + 'static>(
+    client: &M,
+    chain: &ChainConfig,
+    order: OrderComponents,
+    signature: Vec<u8>,
+    fill: &FillAmount,
+    extra_data: Vec<u8>,
+    fulfiller_conduit_key: B256,
+) -> eyre::Result<TransactionReceipt> {
+    let (numerator, denominator) = fill.to_fraction(&order)?;
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    let value = order.max_native_value(numerator, denominator, now)?;
+    let advanced_order = order.to_advanced_order(numerator, denominator, signature, extra_data)?;
+    let calldata = encode_fulfill_advanced_order(
+        advanced_order,
+        vec![],
+        fulfiller_conduit_key,
+        AlloyAddress::ZERO,
+    );
+    send_seaport_transaction(client, chain, calldata, value).await
+}
+
+/// Settles `order_match` on Seaport in one `matchOrders` call, so either every order fills or
+/// none do. The match's surplus goes to the client's signer.
+pub async fn match_orders<M: Middleware + 'static>(
+    client: &M,
+    chain: &ChainConfig,
+    order_match: &OrderMatch,
+) -> eyre::Result<TransactionReceipt> {
+    send_seaport_transaction(client, chain, order_match.calldata(), AlloyU256::ZERO).await
+}
+
+/// How much of `token` Seaport can move for `owner` in orders using `conduit_key`, i.e. what
+/// `owner` has approved to the key's approval target.
+pub async fn allowance<M: Middleware + 'static>(
+    client: Arc<M>,
+    chain: &ChainConfig,
+    token: AlloyAddress,
+    owner: AlloyAddress,
+    conduit_key: B256,
+) -> eyre::Result<AlloyU256> {
+    let spender = chain.approval_target(conduit_key);
+    let allowance = IERC20::new(Address::from_slice(token.as_slice()), client)
+        .allowance(
+            Address::from_slice(owner.as_slice()),
+            Address::from_slice(spender.as_slice()),
+        )
+        .call()
+        .await?;
+    Ok(AlloyU256::from_limbs(allowance.0))
+}
+
+/// Approves `amount` of `token` for the signer's orders using `conduit_key`, so Seaport (or
+/// the conduit) can move it when they are made or taken.
+pub async fn approve<M: Middleware + 'static>(
+    client: Arc<M>,
+    chain: &ChainConfig,
+    token: AlloyAddress,
+    conduit_key: B256,
+    amount: AlloyU256,
+) -> eyre::Result<TransactionReceipt> {
+    let spender = chain.approval_target(conduit_key);
+    let call = IERC20::new(Address::from_slice(token.as_slice()), client).approve(
+        Address::from_slice(spender.as_slice()),
+        U256::from_big_endian(&amount.to_be_bytes::<32>()),
+    );
+    let receipt = call
+        .send()
+        .await?
+        .await?
+        .ok_or(eyre::eyre!("transaction dropped from the mempool."))?;
+
+    if receipt.status != Some(1.into()) {
+        return Err(eyre::eyre!(
+            "approval {:?} of {} reverted.",
+            receipt.transaction_hash,
+            token
+        ));
+    }
+    Ok(receipt)
+}
+
+/// Checks whether each `(order, fill)` in `orders` could be taken right now. The block
+/// timestamp, every offerer's current Seaport counter and their balances and allowances of
+/// the ERC20s they offer are all read in one multicall. The counters read are stored in
+/// `counters`, so later orders are signed and checked against them.
+///
+/// A balance or allowance that can't be read only affects the orders offering that token,
+/// as `Unfillable::UnreadableFunds`.
+pub async fn check_fillability<M: Middleware + 'static>(
+    client: Arc<M>,
+    chain: &ChainConfig,
+    orders: &[(OrderComponents, FillAmount)],
+    counters: &mut CounterCache,
+) -> eyre::Result<Vec<FillabilityVerdict>> {
+    let mut multicall = Multicall::new_with_chain_id(client.clone(), None, Some(chain.chain_id))?;
+    multicall.add_get_current_block_timestamp();
+
+    let seaport = ISeaport::new(
+        Address::from_slice(chain.seaport_address.as_slice()),
+        client.clone(),
+    );
+    let mut offerers: Vec<AlloyAddress> = Vec::new();
+    for (order, _) in orders {
+        if !offerers.contains(&order.offerer) {
+            offerers.push(order.offerer);
+            multicall.add_call(
+                seaport.get_counter(Address::from_slice(order.offerer.as_slice())),
+                true,
+            );
+        }
+    }
+
+    // (offerer, token, spender) in the order their balance and allowance calls are added
+    let mut queried: Vec<(AlloyAddress, AlloyAddress, AlloyAddress)> = Vec::new();
+    for (order, _) in orders {
+        let spender = chain.approval_target(order.conduitKey);
+        let tokens = order
+            .offer
+            .iter()
+            .filter(|item| item.itemType == ItemType::ERC20 as u8)
+            .map(|item| item.token);
+        for token in tokens {
+            if queried.contains(&(order.offerer, token, spender)) {
+                continue;
+            }
+            queried.push((order.offerer, token, spender));
+            let erc20 = IERC20::new(Address::from_slice(token.as_slice()), client.clone());
+            let owner = Address::from_slice(order.offerer.as_slice());
+            // a reverting token mustn't fail the whole batch; its reads come back empty
+            multicall.add_call(erc20.balance_of(owner), true).add_call(
+                erc20.allowance(owner, Address::from_slice(spender.as_slice())),
+                true,
+            );
+        }
+    }
+
+    // `None` for calls that reverted or returned something other than a number
+    let mut values = multicall.call_raw().await?.into_iter().map(|result| {
+        result
+            .ok()
+            .and_then(|token| token.into_uint())
+            .map(|value| AlloyU256::from_limbs(value.0))
+    });
+    let timestamp = values
+        .next()
+        .flatten()
+        .ok_or(eyre::eyre!("multicall: no block timestamp returned."))?;
+    let mut current_counters = HashMap::new();
+    for offerer in offerers {
+        let counter = values.next().flatten().ok_or(eyre::eyre!(
+            "multicall: cannot read the counter of {}.",
+            offerer
+        ))?;
+        counters.insert(chain.chain_id, offerer, counter);
+        current_counters.insert(offerer, counter);
+    }
+    let funds: Vec<TokenFunds> = queried
+        .iter()
+        .map(|_| TokenFunds {
+            balance: values.next().flatten(),
+            allowance: values.next().flatten(),
+        })
+        .collect();
+
+    let mut verdicts = Vec::with_capacity(orders.len());
+    for (order, fill) in orders {
+        let spender = chain.approval_target(order.conduitKey);
+        let order_funds: HashMap<AlloyAddress, TokenFunds> = queried
+            .iter()
+            .zip(&funds)
+            .filter(|((offerer, _, to), _)| (*offerer, *to) == (order.offerer, spender))
+            .map(|((_, token, _), funds)| (*token, *funds))
+            .collect();
+        verdicts.push(FillabilityVerdict::assess(
+            order,
+            chain,
+            fill,
+            timestamp.saturating_to::<u64>(),
+            current_counters[&order.offerer],
+            &order_funds,
+        )?);
+    }
+    Ok(verdicts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+    use aori_types::events::OrderParameters;
+    use ethers::{
+        middleware::SignerMiddleware,
+        prelude::{LocalWallet, Ws},
+        providers::Provider,
+        signers::Signer,
+        utils::Anvil,
+    };
+    use eyre::Context;
+
+    // asks the Seaport behind NODE_URL to hash an order, rather than trusting a copy of its
+    // derivation
+    #[tokio::test]
+    async fn test_order_hash_matches_seaport() {
+        dotenv::dotenv().ok();
+        let node = std::env::var("NODE_URL")
+            .context("missing NODE_URL")
+            .unwrap();
+        let client = Provider::<Ws>::connect(node).await.unwrap();
+
+        let offerer = address!("8f6e1a8a4d0ee1f8e0ff2e6b9b3b8b5d4a79e1c2");
+        let order = OrderParameters::limit_order(
+            offerer,
+            address!("2715Ccea428F8c7694f7e78B2C89cb454c5F7294"),
+            AlloyU256::from(1000000000000000000_u64),
+            address!("D3664B5e72B46eaba722aB6f43c22dBF40181954"),
+            AlloyU256::from(1500000000),
+        )
+        .with_counter(AlloyU256::from(3));
+        let order = OrderComponents::try_from(order).unwrap();
+
+        let chain = ChainConfig::goerli();
+        assert_eq!(
+            get_order_hash(&client, &chain, order.clone())
+                .await
+                .unwrap(),
+            order.order_hash()
+        );
+    }
+
+    // forks the chain behind NODE_URL, where Seaport is deployed
+    #[tokio::test]
+    async fn test_cancel_and_increment_counter() {
+        dotenv::dotenv().ok();
+        let node = std::env::var("NODE_URL")
+            .context("missing NODE_URL")
+            .unwrap();
+        let anvil = Anvil::new().fork(node).spawn();
+        let wallet: LocalWallet = anvil.keys()[0].clone().into();
+        let wallet = wallet.with_chain_id(anvil.chain_id());
+        let offerer = AlloyAddress::from_slice(wallet.address().as_bytes());
+        let client = SignerMiddleware::new(
+            Provider::<Ws>::connect(anvil.ws_endpoint()).await.unwrap(),
+            wallet,
+        );
+
+        let order = OrderParameters::limit_order(
+            offerer,
+            address!("2715Ccea428F8c7694f7e78B2C89cb454c5F7294"),
+            AlloyU256::from(1000000000000000000_u64),
+            address!("D3664B5e72B46eaba722aB6f43c22dBF40181954"),
+            AlloyU256::from(1500000000),
+        )
+        .with_counter(AlloyU256::ZERO);
+        let order = OrderComponents::try_from(order).unwrap();
+
+        let chain = ChainConfig::goerli();
+        let order_hash = order.order_hash();
+        let receipt = cancel_orders(&client, &chain, vec![order]).await.unwrap();
+        assert_eq!(receipt.logs.len(), 1);
+        let status = get_order_status(&client, &chain, order_hash).await.unwrap();
+        assert!(status.isCancelled);
+
+        let counter = get_counter(&client, &chain, offerer).await.unwrap();
+        let receipt = increment_counter(&client, &chain).await.unwrap();
+        assert_eq!(receipt.logs.len(), 1);
+        assert_eq!(
+            get_counter(&client, &chain, offerer).await.unwrap(),
+            counter + AlloyU256::from(1)
+        );
+    }
+}
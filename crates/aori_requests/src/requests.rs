@@ -1,6 +1,12 @@
-use aori_types::seaport::{OrderComponents, SEAPORT_DOMAIN};
+use aori_types::{
+    chains::ChainConfig,
+    constants::DEFAULT_DURATION,
+    fill::FillAmount,
+    market::{Pair, Side},
+    seaport::OrderComponents,
+};
 
-use alloy_primitives::FixedBytes;
+use alloy_primitives::{Address, FixedBytes, U256};
 use alloy_sol_types::SolStruct;
 use ethers::{
     prelude::{k256::ecdsa::SigningKey, Wallet},
@@ -9,9 +15,89 @@ use ethers::{
 };
 
 use serde_json::json;
-use serde_json::Value;
+use serde_json::{Map, Value};
 use std::sync::{Arc, Mutex};
 
+/// How long a new order stays fillable.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TimeInForce {
+    /// Keep the `endTime` the order was built with.
+    #[default]
+    AsBuilt,
+    /// Expire the order this many seconds after its `startTime`. Must be non-zero.
+    Seconds(u64),
+}
+
+/// Options for listing an order through `aori_makeOrder`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MakeOrderOptions {
+    /// Whether the order is shown on the public orderbook.
+    pub is_public: bool,
+    /// The only address allowed to take the order, if any. This is only enforced by the
+    /// Aori servers, not on-chain: anyone holding the signed order can still fill it on
+    /// Seaport directly unless the order's zone restricts who may.
+    pub taker: Option<Address>,
+    pub time_in_force: TimeInForce,
+    /// Any other fields the venue accepts, sent alongside the order. These never override
+    /// the fields set by the SDK itself.
+    pub extra_fields: Map<String, Value>,
+}
+
+impl Default for MakeOrderOptions {
+    fn default() -> Self {
+        Self {
+            is_public: true,
+            taker: None,
+            time_in_force: TimeInForce::AsBuilt,
+            extra_fields: Map::new(),
+        }
+    }
+}
+
+impl MakeOrderOptions {
+    pub fn private() -> Self {
+        Self {
+            is_public: false,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_taker(mut self, taker: Address) -> Self {
+        self.taker = Some(taker);
+        self
+    }
+
+    pub fn with_duration(mut self, seconds: u64) -> Self {
+        self.time_in_force = TimeInForce::Seconds(seconds);
+        self
+    }
+
+    pub fn with_default_duration(self) -> Self {
+        self.with_duration(DEFAULT_DURATION)
+    }
+
+    pub fn with_extra_field(mut self, key: impl Into<String>, value: Value) -> Self {
+        self.extra_fields.insert(key.into(), value);
+        self
+    }
+
+    // endTime is part of the signed order, so this has to run before signing
+    fn apply_to(&self, order_params: &mut OrderComponents) -> eyre::Result<()> {
+        if let TimeInForce::Seconds(seconds) = self.time_in_force {
+            if seconds == 0 {
+                return Err(eyre::eyre!(
+                    "time_in_force: an order must stay open for at least a second."
+                ));
+            }
+            order_params.endTime = order_params
+                .startTime
+                .checked_add(U256::from(seconds))
+                .ok_or(eyre::eyre!("time_in_force: endTime overflows a uint256."))?;
+        }
+        Ok(())
+    }
+}
+
 pub fn create_ping_payload(last_id: &Arc<Mutex<u64>>) -> Value {
     let mut id = last_id.lock().unwrap();
     *id += 1;
@@ -54,13 +140,7 @@ pub fn create_check_auth_payload(last_id: &Arc<Mutex<u64>>, jwt: &str) -> Value
     })
 }
 
-pub fn create_view_orderbook_payload(
-    last_id: &Arc<Mutex<u64>>,
-    chain_id: u64,
-    base: &str,
-    quote: &str,
-    side: &str,
-) -> Value {
+pub fn create_view_orderbook_payload(last_id: &Arc<Mutex<u64>>, pair: &Pair, side: Side) -> Value {
     let mut id = last_id.lock().unwrap();
     *id += 1;
     json!({
@@ -68,12 +148,12 @@ pub fn create_view_orderbook_payload(
         "jsonrpc": "2.0",
         "method": "aori_viewOrderbook",
         "params": [{
-            "chainId": chain_id,
+            "chainId": pair.chain_id,
             "query": {
-                "base": base,
-                "quote": quote,
+                "base": pair.base.to_string(),
+                "quote": pair.quote.to_string(),
             },
-            "side": side // accepts values "BUY" or "SELL"
+            "side": side.to_string()
             // limit: 100 // this is the default, if your application requires it, you can limit the number of orders shown via this parameter
         }]
     })
@@ -82,8 +162,9 @@ pub fn create_view_orderbook_payload(
 pub fn create_make_order_payload(
     last_id: &Arc<Mutex<u64>>,
     wallet: &Wallet<SigningKey>,
-    order_params: OrderComponents,
-    chain_id: u64,
+    mut order_params: OrderComponents,
+    chain: &ChainConfig,
+    options: &MakeOrderOptions,
 ) -> eyre::Result<Value> {
     let new_id = {
         let mut id = last_id.lock().unwrap();
@@ -92,36 +173,52 @@ pub fn create_make_order_payload(
     };
 
     let id = new_id;
-    let sig: FixedBytes<32> = order_params.eip712_signing_hash(&SEAPORT_DOMAIN);
+    options.apply_to(&mut order_params)?;
+    let sig: FixedBytes<32> = order_params.eip712_signing_hash(&chain.domain());
     let signed_sig: Signature = wallet.sign_hash(H256::from_slice(sig.as_slice()))?;
 
+    let mut params = json!({
+        "order": {
+            "signature": format!("0x{}", signed_sig),
+            "parameters": order_params.to_json()
+        },
+        "isPublic": options.is_public,
+        "chainId": chain.chain_id
+    });
+    if let Some(taker) = options.taker {
+        params["taker"] = json!(format!("{}", taker));
+    }
+    if let Value::Object(params) = &mut params {
+        for (key, value) in &options.extra_fields {
+            params.entry(key.clone()).or_insert_with(|| value.clone());
+        }
+    }
+
     Ok(json!({
         "id": id,
         "jsonrpc": "2.0",
         "method": "aori_makeOrder",
-        "params": [{
-            "order": {
-                "signature": format!("0x{}", signed_sig),
-                "parameters": order_params.to_json()
-            },
-            "isPublic": true,
-            "chainId": chain_id
-        }]
+        "params": [params]
     }))
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn create_take_order_payload(
     last_id: &Arc<Mutex<u64>>,
     wallet: &Wallet<SigningKey>,
     order_params: OrderComponents,
+    chain: &ChainConfig,
     order_id: &str,
     seat_id: &str,
     api_key: &str,
+    fill: &FillAmount,
 ) -> eyre::Result<Value> {
+    let (numerator, denominator) = fill.to_fraction(&order_params)?;
+
     let mut id = last_id.lock().unwrap();
     *id += 1;
 
-    let sig: FixedBytes<32> = order_params.eip712_signing_hash(&SEAPORT_DOMAIN);
+    let sig: FixedBytes<32> = order_params.eip712_signing_hash(&chain.domain());
     let signed_sig: Signature = wallet.sign_hash(H256::from_slice(sig.as_slice()))?;
 
     Ok(json!({
@@ -131,7 +228,9 @@ pub fn create_take_order_payload(
         "params": [{
             "order": {
                 "signature": format!("0x{}", signed_sig),
-                "parameters": order_params.to_json()
+                "parameters": order_params.to_json(),
+                "numerator": format!("{}", numerator),
+                "denominator": format!("{}", denominator)
             },
             "orderId": order_id,
             "seatId": seat_id,
@@ -248,3 +347,151 @@ pub fn create_order_status_payload(last_id: &Arc<Mutex<u64>>, order_hash: &str)
 //         }]
 //     })
 // }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+    use aori_types::events::OrderParameters;
+
+    // anvil's first dev account
+    const TEST_KEY: &str = "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+
+    fn sample_order() -> OrderComponents {
+        let mut order = OrderParameters::limit_order(
+            address!("f39Fd6e51aad88F6F4ce6aB8827279cffFb92266"),
+            address!("2715Ccea428F8c7694f7e78B2C89cb454c5F7294"),
+            U256::from(1000000000000000000_u64),
+            address!("D3664B5e72B46eaba722aB6f43c22dBF40181954"),
+            U256::from(1500000000),
+        );
+        order.start_time = U256::from(1697240202);
+        order.end_time = U256::from(1697240262);
+        OrderComponents::try_from(order.with_counter(U256::ZERO)).unwrap()
+    }
+
+    #[test]
+    fn make_order_defaults_to_public() {
+        let wallet = TEST_KEY.parse::<Wallet<SigningKey>>().unwrap();
+        let last_id = Arc::new(Mutex::new(0));
+        let payload = create_make_order_payload(
+            &last_id,
+            &wallet,
+            sample_order(),
+            &ChainConfig::goerli(),
+            &MakeOrderOptions::default(),
+        )
+        .unwrap();
+        let params = &payload["params"][0];
+        assert_eq!(params["isPublic"], true);
+        assert_eq!(params["chainId"], 5);
+        assert!(params.get("taker").is_none());
+        assert_eq!(params["order"]["parameters"]["endTime"], "1697240262");
+    }
+
+    #[test]
+    fn make_order_signs_for_its_chain() {
+        let wallet = TEST_KEY.parse::<Wallet<SigningKey>>().unwrap();
+        let last_id = Arc::new(Mutex::new(0));
+        let options = MakeOrderOptions::default().with_default_duration();
+        let sign = |chain: &ChainConfig| {
+            create_make_order_payload(&last_id, &wallet, sample_order(), chain, &options).unwrap()
+                ["params"][0]
+                .clone()
+        };
+        let mainnet = ChainConfig {
+            chain_id: 1,
+            ..ChainConfig::goerli()
+        };
+        let (goerli, mainnet) = (sign(&ChainConfig::goerli()), sign(&mainnet));
+        assert_eq!(mainnet["chainId"], 1);
+        assert_ne!(goerli["order"]["signature"], mainnet["order"]["signature"]);
+    }
+
+    #[test]
+    fn make_order_with_options() {
+        let wallet = TEST_KEY.parse::<Wallet<SigningKey>>().unwrap();
+        let last_id = Arc::new(Mutex::new(0));
+        let taker = address!("70997970C51812dc3A010C7d01b50e0d17dc79C8");
+        let options = MakeOrderOptions::private()
+            .with_taker(taker)
+            .with_default_duration()
+            .with_extra_field("apiKey", json!("key"))
+            .with_extra_field("isPublic", json!(true));
+        let payload = create_make_order_payload(
+            &last_id,
+            &wallet,
+            sample_order(),
+            &ChainConfig::goerli(),
+            &options,
+        )
+        .unwrap();
+        let params = &payload["params"][0];
+        assert_eq!(params["isPublic"], false);
+        assert_eq!(params["taker"], format!("{}", taker));
+        assert_eq!(params["apiKey"], "key");
+        assert_eq!(
+            params["order"]["parameters"]["endTime"],
+            (1697240202 + DEFAULT_DURATION).to_string()
+        );
+
+        let expired = create_make_order_payload(
+            &last_id,
+            &wallet,
+            sample_order(),
+            &ChainConfig::goerli(),
+            &MakeOrderOptions::default().with_duration(0),
+        );
+        assert!(expired.is_err());
+    }
+
+    #[test]
+    fn take_order_with_partial_fill() {
+        let wallet = TEST_KEY.parse::<Wallet<SigningKey>>().unwrap();
+        let last_id = Arc::new(Mutex::new(0));
+        let payload = create_take_order_payload(
+            &last_id,
+            &wallet,
+            sample_order(),
+            &ChainConfig::goerli(),
+            "0x1234",
+            "0",
+            "key",
+            &FillAmount::OfferAmount(U256::from(250000000000000000_u64)),
+        )
+        .unwrap();
+        let order = &payload["params"][0]["order"];
+        assert_eq!(order["numerator"], "1");
+        assert_eq!(order["denominator"], "4");
+
+        assert!(create_take_order_payload(
+            &last_id,
+            &wallet,
+            sample_order(),
+            &ChainConfig::goerli(),
+            "0x1234",
+            "0",
+            "key",
+            &FillAmount::OfferAmount(U256::from(3)),
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn view_orderbook_for_a_pair() {
+        let last_id = Arc::new(Mutex::new(0));
+        let pair = Pair::new(
+            5,
+            address!("2715Ccea428F8c7694f7e78B2C89cb454c5F7294"),
+            address!("D3664B5e72B46eaba722aB6f43c22dBF40181954"),
+        );
+        let payload = create_view_orderbook_payload(&last_id, &pair, Side::Buy);
+        let params = &payload["params"][0];
+        assert_eq!(params["chainId"], 5);
+        assert_eq!(
+            params["query"]["base"],
+            "0x2715Ccea428F8c7694f7e78B2C89cb454c5F7294"
+        );
+        assert_eq!(params["side"], "BUY");
+    }
+}
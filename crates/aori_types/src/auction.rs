@@ -0,0 +1,231 @@
+use alloy_primitives::U256;
+
+use crate::seaport::{ConsiderationItem, OfferItem, OrderComponents};
+
+/// Which way an order's price moves for whoever takes it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Auction {
+    /// The order gets cheaper to take over time: the offer grows or the consideration shrinks.
+    Descending,
+    /// The order gets more expensive to take over time.
+    Ascending,
+}
+
+/// The amount of an item at `timestamp`, interpolated linearly between its start and end
+/// amounts exactly as Seaport's `_locateCurrentAmount` does. Offer amounts round down and
+/// consideration amounts round up, both in Seaport's favour.
+///
+/// Timestamps outside the order's lifetime are clamped to it. Fails where Seaport's
+/// arithmetic would overflow and revert.
+pub fn current_amount(
+    start_amount: U256,
+    end_amount: U256,
+    start_time: U256,
+    end_time: U256,
+    timestamp: u64,
+    round_up: bool,
+) -> eyre::Result<U256> {
+    if start_amount == end_amount || end_time <= start_time {
+        return Ok(end_amount);
+    }
+
+    let timestamp = U256::from(timestamp).clamp(start_time, end_time);
+    let duration = end_time - start_time;
+    let elapsed = timestamp - start_time;
+    let remaining = duration - elapsed;
+
+    let total_before_division = start_amount
+        .checked_mul(remaining)
+        .zip(end_amount.checked_mul(elapsed))
+        .and_then(|(start, end)| start.checked_add(end))
+        .ok_or(eyre::eyre!(
+            "auction: amounts {} to {} overflow a uint256 over {} seconds.",
+            start_amount,
+            end_amount,
+            duration
+        ))?;
+    if total_before_division.is_zero() {
+        return Ok(U256::ZERO);
+    }
+    let round_up = U256::from(round_up as u8);
+    Ok((total_before_division - round_up) / duration + round_up)
+}
+
+impl OfferItem {
+    pub fn current_amount(
+        &self,
+        start_time: U256,
+        end_time: U256,
+        timestamp: u64,
+    ) -> eyre::Result<U256> {
+        current_amount(
+            self.startAmount,
+            self.endAmount,
+            start_time,
+            end_time,
+            timestamp,
+            false,
+        )
+    }
+}
+
+impl ConsiderationItem {
+    pub fn current_amount(
+        &self,
+        start_time: U256,
+        end_time: U256,
+        timestamp: u64,
+    ) -> eyre::Result<U256> {
+        current_amount(
+            self.startAmount,
+            self.endAmount,
+            start_time,
+            end_time,
+            timestamp,
+            true,
+        )
+    }
+}
+
+impl OrderComponents {
+    pub fn current_offer_amounts(&self, timestamp: u64) -> eyre::Result<Vec<U256>> {
+        self.offer
+            .iter()
+            .map(|item| item.current_amount(self.startTime, self.endTime, timestamp))
+            .collect()
+    }
+
+    pub fn current_consideration_amounts(&self, timestamp: u64) -> eyre::Result<Vec<U256>> {
+        self.consideration
+            .iter()
+            .map(|item| item.current_amount(self.startTime, self.endTime, timestamp))
+            .collect()
+    }
+
+    /// Returns the direction the order's price moves in, or `None` if its amounts are fixed
+    /// or move in opposite directions.
+    pub fn auction(&self) -> Option<Auction> {
+        let offer = self
+            .offer
+            .iter()
+            .map(|item| direction(item.startAmount, item.endAmount, Auction::Descending));
+        let consideration = self
+            .consideration
+            .iter()
+            .map(|item| direction(item.startAmount, item.endAmount, Auction::Ascending));
+
+        let mut auction = None;
+        for item in offer.chain(consideration).flatten() {
+            match auction {
+                None => auction = Some(item),
+                Some(current) if current != item => return None,
+                Some(_) => {}
+            }
+        }
+        auction
+    }
+}
+
+// how an item moves the price for the taker; `rising` is the direction of a growing amount
+fn direction(start_amount: U256, end_amount: U256, rising: Auction) -> Option<Auction> {
+    match start_amount.cmp(&end_amount) {
+        std::cmp::Ordering::Less => Some(rising),
+        std::cmp::Ordering::Greater => Some(match rising {
+            Auction::Descending => Auction::Ascending,
+            Auction::Ascending => Auction::Descending,
+        }),
+        std::cmp::Ordering::Equal => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::order_builder::OrderBuilder;
+    use alloy_primitives::{address, Address};
+
+    const OFFERER: Address = address!("f39Fd6e51aad88F6F4ce6aB8827279cffFb92266");
+    const WETH: Address = address!("2715Ccea428F8c7694f7e78B2C89cb454c5F7294");
+    const USDC: Address = address!("D3664B5e72B46eaba722aB6f43c22dBF40181954");
+
+    #[test]
+    fn interpolates_like_seaport() {
+        let (start, end) = (U256::from(100), U256::from(200));
+        let amount = |timestamp, round_up| {
+            current_amount(
+                U256::from(10),
+                U256::from(1),
+                start,
+                end,
+                timestamp,
+                round_up,
+            )
+            .unwrap()
+        };
+        assert_eq!(amount(100, false), U256::from(10));
+        assert_eq!(amount(200, false), U256::from(1));
+        // 10 - 9 * 0.25 = 7.75
+        assert_eq!(amount(125, false), U256::from(7));
+        assert_eq!(amount(125, true), U256::from(8));
+        // clamped to the order's lifetime
+        assert_eq!(amount(0, false), U256::from(10));
+        assert_eq!(amount(1000, false), U256::from(1));
+        // fixed amounts never move
+        assert_eq!(
+            current_amount(U256::from(5), U256::from(5), start, end, 150, true).unwrap(),
+            U256::from(5)
+        );
+        // Seaport reverts on overflow, so this fails rather than wrapping
+        assert!(current_amount(U256::MAX, U256::from(1), start, end, 150, false).is_err());
+    }
+
+    #[test]
+    fn descending_auction() {
+        let order = OrderBuilder::new(OFFERER)
+            .offer_erc20(WETH, U256::from(1000000000000000000_u128))
+            .consideration_erc20_auction(USDC, U256::from(2000000000), U256::from(1000000000))
+            .start_time(1697240000)
+            .duration(1000)
+            .build()
+            .unwrap();
+        assert_eq!(order.auction(), Some(Auction::Descending));
+        assert_eq!(
+            order.current_consideration_amounts(1697240500).unwrap(),
+            vec![U256::from(1500000000)]
+        );
+        assert_eq!(
+            order.current_offer_amounts(1697240500).unwrap(),
+            vec![U256::from(1000000000000000000_u128)]
+        );
+    }
+
+    #[test]
+    fn ascending_auction() {
+        let order = OrderBuilder::new(OFFERER)
+            .offer_erc20_auction(
+                WETH,
+                U256::from(1000000000000000000_u128),
+                U256::from(500000000000000000_u128),
+            )
+            .consideration_erc20(USDC, U256::from(1500000000))
+            .start_time(1697240000)
+            .duration(1000)
+            .build()
+            .unwrap();
+        assert_eq!(order.auction(), Some(Auction::Ascending));
+        assert_eq!(
+            order.current_offer_amounts(1697240250).unwrap(),
+            vec![U256::from(875000000000000000_u128)]
+        );
+    }
+
+    #[test]
+    fn fixed_price_is_not_an_auction() {
+        let order = OrderBuilder::new(OFFERER)
+            .offer_erc20(WETH, U256::from(1))
+            .consideration_erc20(USDC, U256::from(1))
+            .build()
+            .unwrap();
+        assert_eq!(order.auction(), None);
+    }
+}
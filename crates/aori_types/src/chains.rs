@@ -0,0 +1,308 @@
+use std::{collections::HashMap, path::Path};
+
+use alloy_primitives::{address, Address, B256};
+use alloy_sol_types::{eip712_domain, Eip712Domain};
+use serde::Deserialize;
+
+use crate::constants::{CONDUIT_CREATION_CODE_HASH, DEFAULT_CHAIN_ID, DEFAULT_CONDUIT_KEY};
+
+/// Everything that differs between the chains Aori trades on: where Seaport and the Aori
+/// zone live, which conduits orders may route approvals through, and how to reach the API.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChainConfig {
+    pub chain_id: u64,
+    pub name: String,
+    pub seaport_address: Address,
+    pub seaport_version: String,
+    /// The zone Aori orders are restricted to.
+    pub zone: Address,
+    /// Conduit keys orders may use. The first one is the default; the zero key means
+    /// approving Seaport itself.
+    pub conduit_keys: Vec<B256>,
+    /// The `ConduitController` that deploys the conduits behind those keys.
+    pub conduit_controller: Address,
+    /// The ERC20 wrapper of the chain's native token, e.g. WETH.
+    pub wrapped_native: Address,
+    pub request_url: String,
+    pub feed_url: String,
+}
+
+impl ChainConfig {
+    /// Goerli, where the Aori dev API runs.
+    pub fn goerli() -> Self {
+        Self {
+            chain_id: 5,
+            name: "goerli".to_string(),
+            seaport_address: address!("00000000000000adc04c56bf30ac9d3c0aaf14dc"),
+            seaport_version: "1.5".to_string(),
+            zone: address!("eA2b4e7F02b859305093f9F4778a19D66CA176d5"),
+            conduit_keys: vec![DEFAULT_CONDUIT_KEY.into()],
+            conduit_controller: address!("00000000F9490004C11Cef243f5400493c00Ad63"),
+            wrapped_native: address!("2715Ccea428F8c7694f7e78B2C89cb454c5F7294"),
+            request_url: "wss://dev.api.beta.order.aori.io/".to_string(),
+            feed_url: "wss://dev.beta.feed.aori.io/".to_string(),
+        }
+    }
+
+    /// The EIP-712 domain orders on this chain are signed under.
+    pub fn domain(&self) -> Eip712Domain {
+        eip712_domain! {
+            name: String::from("Seaport"),
+            version: self.seaport_version.clone(),
+            chain_id: self.chain_id,
+            verifying_contract: self.seaport_address,
+        }
+    }
+
+    pub fn default_conduit_key(&self) -> B256 {
+        self.conduit_keys
+            .first()
+            .copied()
+            .unwrap_or(DEFAULT_CONDUIT_KEY.into())
+    }
+
+    pub fn supports_conduit_key(&self, conduit_key: B256) -> bool {
+        self.conduit_keys.contains(&conduit_key)
+    }
+
+    /// Where the conduit for `conduit_key` lives, whether or not it has been deployed yet.
+    pub fn conduit_address(&self, conduit_key: B256) -> Address {
+        self.conduit_controller
+            .create2(conduit_key, CONDUIT_CREATION_CODE_HASH)
+    }
+
+    /// The address that moves tokens for orders using `conduit_key`, and so the one offerers
+    /// and fulfillers must approve: Seaport itself for the zero key, otherwise the conduit.
+    pub fn approval_target(&self, conduit_key: B256) -> Address {
+        if conduit_key.is_zero() {
+            self.seaport_address
+        } else {
+            self.conduit_address(conduit_key)
+        }
+    }
+}
+
+impl Default for ChainConfig {
+    fn default() -> Self {
+        Self::goerli()
+    }
+}
+
+// one entry of an overrides file; every field but the chain id is optional, so a file only
+// needs to list what it changes
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ChainOverride {
+    chain_id: u64,
+    name: Option<String>,
+    seaport_address: Option<String>,
+    seaport_version: Option<String>,
+    zone: Option<String>,
+    conduit_keys: Option<Vec<String>>,
+    conduit_controller: Option<String>,
+    wrapped_native: Option<String>,
+    request_url: Option<String>,
+    feed_url: Option<String>,
+}
+
+impl ChainOverride {
+    fn apply_to(self, chain: &mut ChainConfig) -> eyre::Result<()> {
+        fn parse<T: std::str::FromStr>(field: &str, value: &str) -> eyre::Result<T> {
+            value
+                .parse::<T>()
+                .map_err(|_| eyre::eyre!("{}: invalid value {}.", field, value))
+        }
+
+        if let Some(name) = self.name {
+            chain.name = name;
+        }
+        if let Some(seaport_address) = self.seaport_address {
+            chain.seaport_address = parse("seaportAddress", &seaport_address)?;
+        }
+        if let Some(seaport_version) = self.seaport_version {
+            chain.seaport_version = seaport_version;
+        }
+        if let Some(zone) = self.zone {
+            chain.zone = parse("zone", &zone)?;
+        }
+        if let Some(conduit_keys) = self.conduit_keys {
+            chain.conduit_keys = conduit_keys
+                .iter()
+                .map(|key| parse("conduitKeys", key))
+                .collect::<eyre::Result<_>>()?;
+        }
+        if let Some(conduit_controller) = self.conduit_controller {
+            chain.conduit_controller = parse("conduitController", &conduit_controller)?;
+        }
+        if let Some(wrapped_native) = self.wrapped_native {
+            chain.wrapped_native = parse("wrappedNative", &wrapped_native)?;
+        }
+        if let Some(request_url) = self.request_url {
+            chain.request_url = request_url;
+        }
+        if let Some(feed_url) = self.feed_url {
+            chain.feed_url = feed_url;
+        }
+        Ok(())
+    }
+
+    // a chain without a preset has nothing to fall back on for its contracts
+    fn is_complete(&self) -> bool {
+        self.seaport_address.is_some() && self.zone.is_some() && self.wrapped_native.is_some()
+    }
+}
+
+/// Chain configurations, keyed by chain id.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ChainRegistry {
+    chains: HashMap<u64, ChainConfig>,
+}
+
+impl ChainRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The chains that come built in. Only Goerli is, since that's where the Aori dev API
+    /// runs; other chains are added through overrides.
+    pub fn presets() -> Self {
+        Self::new().with_chain(ChainConfig::goerli())
+    }
+
+    /// Applies a JSON list of `{ chainId, ... }` overrides on top of these chains. Known
+    /// chains only need the fields that change; new chains need at least `seaportAddress`,
+    /// `zone` and `wrappedNative`, and take everything else from the default chain.
+    pub fn with_overrides(mut self, json: &str) -> eyre::Result<Self> {
+        for entry in serde_json::from_str::<Vec<ChainOverride>>(json)? {
+            let mut chain = match self.chains.remove(&entry.chain_id) {
+                Some(chain) => chain,
+                None if entry.is_complete() => ChainConfig {
+                    chain_id: entry.chain_id,
+                    name: entry.chain_id.to_string(),
+                    ..ChainConfig::default()
+                },
+                None => {
+                    return Err(eyre::eyre!(
+                        "chains: chain {} has no preset, so it needs a seaportAddress, zone and wrappedNative.",
+                        entry.chain_id
+                    ))
+                }
+            };
+            entry.apply_to(&mut chain)?;
+            self.insert(chain);
+        }
+        Ok(self)
+    }
+
+    pub fn load_overrides(self, path: impl AsRef<Path>) -> eyre::Result<Self> {
+        self.with_overrides(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn with_chain(mut self, chain: ChainConfig) -> Self {
+        self.insert(chain);
+        self
+    }
+
+    pub fn insert(&mut self, chain: ChainConfig) {
+        self.chains.insert(chain.chain_id, chain);
+    }
+
+    pub fn get(&self, chain_id: u64) -> Option<&ChainConfig> {
+        self.chains.get(&chain_id)
+    }
+
+    /// Like `get`, for callers that can't go on without the chain.
+    pub fn chain(&self, chain_id: u64) -> eyre::Result<&ChainConfig> {
+        self.get(chain_id)
+            .ok_or(eyre::eyre!("chains: chain {} is not configured.", chain_id))
+    }
+
+    /// The chain to use when none has been picked yet.
+    pub fn default_chain(&self) -> eyre::Result<&ChainConfig> {
+        self.chain(DEFAULT_CHAIN_ID)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &ChainConfig> {
+        self.chains.values()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::b256;
+
+    #[test]
+    fn domains_differ_per_chain() {
+        let chains = ChainRegistry::presets().with_chain(ChainConfig {
+            chain_id: 1,
+            ..ChainConfig::goerli()
+        });
+        let goerli = chains.chain(5).unwrap().domain();
+        let mainnet = chains.chain(1).unwrap().domain();
+        assert_ne!(goerli.separator(), mainnet.separator());
+        assert_eq!(goerli.verifying_contract, mainnet.verifying_contract);
+        assert!(chains.chain(10).is_err());
+    }
+
+    #[test]
+    fn overrides_presets_and_adds_chains() {
+        let chains = ChainRegistry::presets()
+            .with_overrides(
+                r#"[
+                    {
+                        "chainId": 5,
+                        "requestUrl": "ws://localhost:8080/",
+                        "conduitKeys": ["0x0000007b02230091a7ed01230072f7006a004d60a8d4e71d599b8104250f0000"]
+                    },
+                    {
+                        "chainId": 42161,
+                        "name": "arbitrum",
+                        "seaportAddress": "0x00000000000000adc04c56bf30ac9d3c0aaf14dc",
+                        "zone": "0xeA2b4e7F02b859305093f9F4778a19D66CA176d5",
+                        "wrappedNative": "0x82aF49447D8a07e3bd95BD0d56f35241523fBab1"
+                    }
+                ]"#,
+            )
+            .unwrap();
+
+        let goerli = chains.chain(5).unwrap();
+        assert_eq!(goerli.request_url, "ws://localhost:8080/");
+        assert_eq!(goerli.feed_url, ChainConfig::goerli().feed_url);
+        assert_eq!(
+            goerli.default_conduit_key(),
+            b256!("0000007b02230091a7ed01230072f7006a004d60a8d4e71d599b8104250f0000")
+        );
+
+        let arbitrum = chains.chain(42161).unwrap();
+        assert_eq!(arbitrum.name, "arbitrum");
+        assert_eq!(arbitrum.domain().chain_id, Some(42161.try_into().unwrap()));
+    }
+
+    #[test]
+    fn resolves_approval_targets() {
+        let chain = ChainConfig::goerli();
+        let opensea = b256!("0000007b02230091a7ed01230072f7006a004d60a8d4e71d599b8104250f0000");
+        assert_eq!(
+            chain.conduit_address(opensea),
+            address!("1E0049783F008A0085193E00003D00cd54003c71")
+        );
+        assert_eq!(
+            chain.approval_target(opensea),
+            address!("1E0049783F008A0085193E00003D00cd54003c71")
+        );
+        assert_eq!(chain.approval_target(B256::ZERO), chain.seaport_address);
+    }
+
+    #[test]
+    fn rejects_incomplete_overrides() {
+        assert!(ChainRegistry::presets()
+            .with_overrides(
+                r#"[{ "chainId": 10, "zone": "0xeA2b4e7F02b859305093f9F4778a19D66CA176d5" }]"#
+            )
+            .is_err());
+        assert!(ChainRegistry::presets()
+            .with_overrides(r#"[{ "chainId": 5, "zone": "aori" }]"#)
+            .is_err());
+    }
+}
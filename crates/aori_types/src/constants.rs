@@ -1,13 +1,15 @@
-use alloy_primitives::{address, hex, Address};
+use alloy_primitives::hex;
 
-pub static DEFAULT_ORDER_ADDRESS: Address = address!("eA2b4e7F02b859305093f9F4778a19D66CA176d5");
+// chain-specific addresses and endpoints live in `chains::ChainConfig`
+
+/// The chain used when nothing else picks one: Goerli, where the Aori dev API runs.
+pub static DEFAULT_CHAIN_ID: u64 = 5;
 pub static DEFAULT_ZONE_HASH: [u8; 32] =
     hex!("0000000000000000000000000000000000000000000000000000000000000000");
-pub static DEFAULT_DURATION: i32 = 86400000_i32;
-pub static CURRENT_SEAPORT_ADDRESS: Address = address!("00000000000000adc04c56bf30ac9d3c0aaf14dc");
-pub static CURRENT_SEAPORT_VERSION: &str = "1.5";
+pub static DEFAULT_DURATION: u64 = 86400; // seconds, as Seaport's startTime/endTime are
 pub static DEFAULT_CONDUIT_KEY: [u8; 32] =
     hex!("0000000000000000000000000000000000000000000000000000000000000000");
-
-pub static REQUEST_URL: &str = "wss://dev.api.beta.order.aori.io/";
-pub static MARKET_FEED_URL: &str = "wss://dev.beta.feed.aori.io/";
+/// keccak256 of the creation code of Seaport conduits. `ConduitController` deploys each one
+/// with CREATE2, salted with its conduit key.
+pub static CONDUIT_CREATION_CODE_HASH: [u8; 32] =
+    hex!("023d904f2503c37127200ca07b976c3a53cc562623f67023115bf311f5805059");
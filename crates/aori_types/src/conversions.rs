@@ -0,0 +1,338 @@
+use std::fmt;
+
+use crate::{events, seaport};
+
+/// Why an order couldn't be converted between its Aori event form and its Seaport form.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConversionError {
+    /// The offending field, e.g. `counter`.
+    pub field: String,
+    pub reason: String,
+}
+
+impl ConversionError {
+    fn new(field: impl Into<String>, reason: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            reason: reason.into(),
+        }
+    }
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}.", self.field, self.reason)
+    }
+}
+
+impl std::error::Error for ConversionError {}
+
+impl From<&events::OfferItem> for seaport::OfferItem {
+    fn from(item: &events::OfferItem) -> Self {
+        Self {
+            itemType: item.item_type as u8,
+            token: item.token,
+            identifierOrCriteria: item.identifier_or_criteria,
+            startAmount: item.start_amount,
+            endAmount: item.end_amount,
+        }
+    }
+}
+
+impl From<&events::ConsiderationItem> for seaport::ConsiderationItem {
+    fn from(item: &events::ConsiderationItem) -> Self {
+        Self {
+            itemType: item.item_type as u8,
+            token: item.token,
+            identifierOrCriteria: item.identifier_or_criteria,
+            startAmount: item.start_amount,
+            endAmount: item.end_amount,
+            recipient: item.recipient,
+        }
+    }
+}
+
+/// Converts the order as the Aori API reports it into the `OrderComponents` its offerer
+/// signed. Fails if the counter is unknown (see `OrderParameters::with_counter`), since
+/// guessing it would produce the wrong order hash.
+///
+/// Consideration items past `totalOriginalConsiderationItems` are tips added by a fulfiller
+/// and aren't part of the signed order, so they are left out.
+impl TryFrom<&events::OrderParameters> for seaport::OrderComponents {
+    type Error = ConversionError;
+
+    fn try_from(order: &events::OrderParameters) -> Result<Self, Self::Error> {
+        let original_items = usize::try_from(order.total_original_consideration_items)
+            .ok()
+            .filter(|count| *count <= order.consideration.len())
+            .ok_or(ConversionError::new(
+                "totalOriginalConsiderationItems",
+                format!(
+                    "{} is not within the {} consideration items",
+                    order.total_original_consideration_items,
+                    order.consideration.len()
+                ),
+            ))?;
+        let counter = order
+            .counter
+            .ok_or(ConversionError::new("counter", "missing"))?;
+
+        Ok(Self {
+            offerer: order.offerer,
+            zone: order.zone,
+            offer: order.offer.iter().map(seaport::OfferItem::from).collect(),
+            consideration: order.consideration[..original_items]
+                .iter()
+                .map(seaport::ConsiderationItem::from)
+                .collect(),
+            orderType: order.order_type as u8,
+            startTime: order.start_time,
+            endTime: order.end_time,
+            zoneHash: order.zone_hash,
+            salt: order.salt,
+            conduitKey: order.conduit_key,
+            counter,
+        })
+    }
+}
+
+impl TryFrom<events::OrderParameters> for seaport::OrderComponents {
+    type Error = ConversionError;
+
+    fn try_from(order: events::OrderParameters) -> Result<Self, Self::Error> {
+        Self::try_from(&order)
+    }
+}
+
+impl TryFrom<&seaport::OfferItem> for events::OfferItem {
+    type Error = ConversionError;
+
+    fn try_from(item: &seaport::OfferItem) -> Result<Self, Self::Error> {
+        Ok(Self::new(
+            item_type(item.itemType)?,
+            item.token,
+            item.identifierOrCriteria,
+            item.startAmount,
+            item.endAmount,
+        ))
+    }
+}
+
+impl TryFrom<&seaport::ConsiderationItem> for events::ConsiderationItem {
+    type Error = ConversionError;
+
+    fn try_from(item: &seaport::ConsiderationItem) -> Result<Self, Self::Error> {
+        Ok(Self::new(
+            item_type(item.itemType)?,
+            item.token,
+            item.identifierOrCriteria,
+            item.startAmount,
+            item.endAmount,
+            item.recipient,
+        ))
+    }
+}
+
+/// Converts signed `OrderComponents` into the form the Aori API uses, keeping the counter.
+/// Fails for item or order types Seaport doesn't define, and for orders with more
+/// consideration items than the API's count can hold.
+impl TryFrom<&seaport::OrderComponents> for events::OrderParameters {
+    type Error = ConversionError;
+
+    fn try_from(order: &seaport::OrderComponents) -> Result<Self, Self::Error> {
+        let total_original_consideration_items =
+            i16::try_from(order.consideration.len()).map_err(|_| {
+                ConversionError::new(
+                    "consideration",
+                    format!("{} items do not fit in an i16", order.consideration.len()),
+                )
+            })?;
+
+        Ok(Self {
+            offerer: order.offerer,
+            zone: order.zone,
+            offer: order
+                .offer
+                .iter()
+                .map(events::OfferItem::try_from)
+                .collect::<Result<_, _>>()?,
+            consideration: order
+                .consideration
+                .iter()
+                .map(events::ConsiderationItem::try_from)
+                .collect::<Result<_, _>>()?,
+            order_type: seaport::OrderType::try_from(order.orderType).map_err(|_| {
+                ConversionError::new(
+                    "orderType",
+                    format!("unknown order type {}", order.orderType),
+                )
+            })?,
+            start_time: order.startTime,
+            end_time: order.endTime,
+            zone_hash: order.zoneHash,
+            salt: order.salt,
+            conduit_key: order.conduitKey,
+            total_original_consideration_items,
+            counter: Some(order.counter),
+        })
+    }
+}
+
+impl TryFrom<seaport::OrderComponents> for events::OrderParameters {
+    type Error = ConversionError;
+
+    fn try_from(order: seaport::OrderComponents) -> Result<Self, Self::Error> {
+        Self::try_from(&order)
+    }
+}
+
+fn item_type(item_type: u8) -> Result<seaport::ItemType, ConversionError> {
+    seaport::ItemType::try_from(item_type)
+        .map_err(|_| ConversionError::new("itemType", format!("unknown item type {}", item_type)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        criteria::criteria_root,
+        order_builder::OrderBuilder,
+        seaport::{ItemType, OrderComponents, OrderType},
+    };
+    use alloy_primitives::{address, b256, Address, U256};
+
+    const OFFERER: Address = address!("f39Fd6e51aad88F6F4ce6aB8827279cffFb92266");
+    const PROTOCOL: Address = address!("70997970C51812dc3A010C7d01b50e0d17dc79C8");
+    const WETH: Address = address!("2715Ccea428F8c7694f7e78B2C89cb454c5F7294");
+    const USDC: Address = address!("D3664B5e72B46eaba722aB6f43c22dBF40181954");
+    const NFT: Address = address!("5FbDB2315678afecb367f032d93F642f64180aa3");
+
+    // sol! structs don't implement PartialEq; the order hash commits to every field
+    fn assert_same_order(a: &OrderComponents, b: &OrderComponents) {
+        assert_eq!(a.order_hash(), b.order_hash());
+        assert_eq!(a.to_json(), b.to_json());
+    }
+
+    // an order that sets every field to something other than its default
+    fn busy_order() -> OrderComponents {
+        OrderBuilder::new(OFFERER)
+            .offer_erc20_auction(
+                WETH,
+                U256::from(100000000000000000000_u128),
+                U256::from(90000000000000000000_u128),
+            )
+            .offer_erc721(NFT, U256::from(1234))
+            .consideration_erc20(USDC, U256::from(1500000000_u64))
+            .consideration_erc1155_criteria(
+                NFT,
+                criteria_root(&[U256::from(1), U256::from(2), U256::from(3)]),
+                U256::from(5),
+            )
+            .consideration_native_to(U256::from(1000), PROTOCOL)
+            .fee(PROTOCOL, 30)
+            .order_type(OrderType::FULL_OPEN)
+            .zone(PROTOCOL)
+            .zone_hash(b256!(
+                "1111111111111111111111111111111111111111111111111111111111111111"
+            ))
+            .conduit_key(b256!(
+                "0000007b02230091a7ed01230072f7006a004d60a8d4e71d599b8104250f0000"
+            ))
+            .start_time(1697240202)
+            .duration(600)
+            .salt(U256::MAX)
+            .counter(U256::from(7))
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn components_round_trip() {
+        let order = busy_order();
+        let parameters = events::OrderParameters::try_from(&order).unwrap();
+        assert_eq!(parameters.total_original_consideration_items, 4);
+        assert_eq!(parameters.counter, Some(U256::from(7)));
+
+        let converted = OrderComponents::try_from(&parameters).unwrap();
+        assert_same_order(&converted, &order);
+    }
+
+    #[test]
+    fn parameters_round_trip() {
+        let parameters = events::OrderParameters::try_from(busy_order()).unwrap();
+        let json = serde_json::to_value(&parameters).unwrap();
+        let parsed: events::OrderParameters = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, parameters);
+
+        let again =
+            events::OrderParameters::try_from(OrderComponents::try_from(&parsed).unwrap()).unwrap();
+        assert_eq!(again, parameters);
+    }
+
+    #[test]
+    fn parses_lowercase_and_unprefixed_values() {
+        let parameters = events::OrderParameters::try_from(busy_order()).unwrap();
+        let mut json = serde_json::to_value(&parameters).unwrap();
+        json["offerer"] = parameters.offerer.to_string().to_lowercase().into();
+        json["salt"] = format!("{:x}", parameters.salt).into();
+        json["offer"][1]["identifierOrCriteria"] = "0x4d2".into();
+        json["consideration"][0]["startAmount"] = 1495500000.into();
+
+        let parsed: events::OrderParameters = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, parameters);
+        assert_same_order(&OrderComponents::try_from(&parsed).unwrap(), &busy_order());
+    }
+
+    #[test]
+    fn unknown_item_and_order_types() {
+        let parameters = events::OrderParameters::try_from(busy_order()).unwrap();
+        let json = serde_json::to_value(&parameters).unwrap();
+        assert_eq!(json["orderType"], 0);
+        assert_eq!(json["offer"][1]["itemType"], 2);
+
+        let mut bad_order_type = json.clone();
+        bad_order_type["orderType"] = 5.into();
+        assert!(serde_json::from_value::<events::OrderParameters>(bad_order_type).is_err());
+        let mut bad_item_type = json;
+        bad_item_type["consideration"][0]["itemType"] = 6.into();
+        assert!(serde_json::from_value::<events::OrderParameters>(bad_item_type).is_err());
+
+        let mut order = busy_order();
+        order.offer[0].itemType = 6;
+        let err = events::OrderParameters::try_from(&order).unwrap_err();
+        assert_eq!(err.to_string(), "itemType: unknown item type 6.");
+        let mut order = busy_order();
+        order.orderType = 9;
+        let err = events::OrderParameters::try_from(&order).unwrap_err();
+        assert_eq!(err.field, "orderType");
+    }
+
+    #[test]
+    fn tips_are_not_part_of_the_signed_order() {
+        let order = busy_order();
+        let mut parameters = events::OrderParameters::try_from(&order).unwrap();
+        parameters
+            .consideration
+            .push(events::ConsiderationItem::new(
+                ItemType::ERC20,
+                USDC,
+                U256::ZERO,
+                U256::from(1),
+                U256::from(1),
+                PROTOCOL,
+            ));
+        assert_same_order(&OrderComponents::try_from(&parameters).unwrap(), &order);
+
+        parameters.total_original_consideration_items = 6;
+        let err = OrderComponents::try_from(&parameters).unwrap_err();
+        assert_eq!(err.field, "totalOriginalConsiderationItems");
+    }
+
+    #[test]
+    fn missing_counter() {
+        let mut parameters = events::OrderParameters::try_from(busy_order()).unwrap();
+        parameters.counter = None;
+        let err = OrderComponents::try_from(&parameters).unwrap_err();
+        assert_eq!(err.to_string(), "counter: missing.");
+    }
+}
@@ -0,0 +1,171 @@
+use alloy_primitives::{keccak256, B256, U256};
+
+use crate::seaport::{CriteriaResolver, Side};
+
+// Criteria trees follow Seaport's `_verifyProof`: leaves are the hashed 32-byte token ids and
+// every pair is hashed in sorted order, so proofs don't need to say which side a node is on.
+
+fn hash_leaf(token_id: U256) -> B256 {
+    keccak256(token_id.to_be_bytes::<32>())
+}
+
+fn hash_pair(a: B256, b: B256) -> B256 {
+    let (low, high) = if a <= b { (a, b) } else { (b, a) };
+    let mut packed = [0u8; 64];
+    packed[..32].copy_from_slice(low.as_slice());
+    packed[32..].copy_from_slice(high.as_slice());
+    keccak256(packed)
+}
+
+// every level of the tree, leaves first; an odd node out is carried up unchanged
+fn tree_levels(token_ids: &[U256]) -> Vec<Vec<B256>> {
+    let mut leaves: Vec<B256> = token_ids.iter().map(|id| hash_leaf(*id)).collect();
+    leaves.sort();
+    leaves.dedup();
+
+    let mut levels = vec![leaves];
+    while levels.last().is_some_and(|level| level.len() > 1) {
+        let next = levels
+            .last()
+            .unwrap()
+            .chunks(2)
+            .map(|pair| match pair {
+                [a, b] => hash_pair(*a, *b),
+                [a] => *a,
+                _ => unreachable!(),
+            })
+            .collect();
+        levels.push(next);
+    }
+    levels
+}
+
+/// The merkle root committing to `token_ids`, for use as the `identifierOrCriteria` of a
+/// criteria-based item. An empty set gives a zero root, which Seaport treats as "any token".
+pub fn criteria_root(token_ids: &[U256]) -> U256 {
+    tree_levels(token_ids)
+        .last()
+        .and_then(|level| level.first())
+        .map_or(U256::ZERO, |root| U256::from_be_bytes(root.0))
+}
+
+/// The proof that `token_id` is part of the set committed to by `criteria_root(token_ids)`.
+pub fn criteria_proof(token_ids: &[U256], token_id: U256) -> Option<Vec<B256>> {
+    let levels = tree_levels(token_ids);
+    let mut index = levels.first()?.binary_search(&hash_leaf(token_id)).ok()?;
+
+    let mut proof = Vec::new();
+    for level in &levels[..levels.len() - 1] {
+        if let Some(sibling) = level.get(index ^ 1) {
+            proof.push(*sibling);
+        }
+        index /= 2;
+    }
+    Some(proof)
+}
+
+pub fn verify_criteria_proof(root: U256, token_id: U256, proof: &[B256]) -> bool {
+    let computed = proof.iter().fold(hash_leaf(token_id), |node, sibling| {
+        hash_pair(node, *sibling)
+    });
+    U256::from_be_bytes(computed.0) == root
+}
+
+/// Builds the resolver that tells Seaport which token fills the criteria item at `index` on
+/// `side` of the order at `order_index`. Items with a zero (wildcard) root need no proof.
+pub fn criteria_resolver(
+    order_index: usize,
+    side: Side,
+    index: usize,
+    token_ids: &[U256],
+    token_id: U256,
+) -> Option<CriteriaResolver> {
+    let criteria_proof = if token_ids.is_empty() {
+        Vec::new()
+    } else {
+        criteria_proof(token_ids, token_id)?
+    };
+    Some(CriteriaResolver {
+        orderIndex: U256::from(order_index),
+        side,
+        index: U256::from(index),
+        identifier: token_id,
+        criteriaProof: criteria_proof,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token_ids(count: u64) -> Vec<U256> {
+        (1..=count).map(U256::from).collect()
+    }
+
+    #[test]
+    fn single_token_root_is_its_leaf() {
+        let root = criteria_root(&[U256::from(7)]);
+        assert_eq!(root, U256::from_be_bytes(hash_leaf(U256::from(7)).0));
+        assert_eq!(
+            criteria_proof(&[U256::from(7)], U256::from(7)),
+            Some(vec![])
+        );
+        assert!(verify_criteria_proof(root, U256::from(7), &[]));
+    }
+
+    #[test]
+    fn two_token_root() {
+        let root = criteria_root(&token_ids(2));
+        let expected = hash_pair(hash_leaf(U256::from(1)), hash_leaf(U256::from(2)));
+        assert_eq!(root, U256::from_be_bytes(expected.0));
+    }
+
+    #[test]
+    fn proofs_verify_for_every_member() {
+        for count in [2, 3, 5, 8, 13] {
+            let ids = token_ids(count);
+            let root = criteria_root(&ids);
+            for id in &ids {
+                let proof = criteria_proof(&ids, *id).unwrap();
+                assert!(verify_criteria_proof(root, *id, &proof), "{count} {id}");
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_non_members() {
+        let ids = token_ids(5);
+        let root = criteria_root(&ids);
+        assert_eq!(criteria_proof(&ids, U256::from(6)), None);
+
+        let proof = criteria_proof(&ids, U256::from(1)).unwrap();
+        assert!(!verify_criteria_proof(root, U256::from(6), &proof));
+    }
+
+    #[test]
+    fn order_and_duplicates_do_not_matter() {
+        let ids = token_ids(5);
+        let mut shuffled = vec![ids[3], ids[0], ids[4], ids[1], ids[2], ids[0]];
+        assert_eq!(criteria_root(&ids), criteria_root(&shuffled));
+        shuffled.pop();
+        assert_eq!(criteria_root(&ids), criteria_root(&shuffled));
+    }
+
+    #[test]
+    fn resolver_for_wildcard_and_rooted_criteria() {
+        let wildcard = criteria_resolver(0, Side::CONSIDERATION, 0, &[], U256::from(9)).unwrap();
+        assert!(wildcard.criteriaProof.is_empty());
+        assert_eq!(wildcard.identifier, U256::from(9));
+
+        let ids = token_ids(4);
+        let resolver = criteria_resolver(1, Side::OFFER, 2, &ids, U256::from(3)).unwrap();
+        assert_eq!(resolver.orderIndex, U256::from(1));
+        assert_eq!(resolver.index, U256::from(2));
+        assert!(verify_criteria_proof(
+            criteria_root(&ids),
+            U256::from(3),
+            &resolver.criteriaProof
+        ));
+        assert!(criteria_resolver(0, Side::OFFER, 0, &ids, U256::from(9)).is_none());
+    }
+}
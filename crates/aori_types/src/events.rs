@@ -1,13 +1,17 @@
 use chrono::Utc;
-use serde::{Deserialize, Deserializer, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize};
 use serde_json::Value;
 
 use crate::{
-    constants::{DEFAULT_CONDUIT_KEY, DEFAULT_ORDER_ADDRESS, DEFAULT_ZONE_HASH},
+    chains::ChainConfig,
+    constants::{DEFAULT_DURATION, DEFAULT_ZONE_HASH},
+    conversions::ConversionError,
+    fee::{bps_of, MAX_BPS},
     seaport,
+    serde_utils::{hex_u256, option_string, option_u256_amount, sol_enum, string, u256_amount},
 };
 
-use alloy_primitives::{Address, U256};
+use alloy_primitives::{Address, B256, U256};
 
 // Struct representing the outermost layer of JSON
 #[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
@@ -26,172 +30,213 @@ pub struct OrderCreationData {
 
 #[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
 pub struct OrderParameters {
-    pub offerer: String,
-    pub zone: String,
+    #[serde(with = "string")]
+    pub offerer: Address,
+    #[serde(with = "string")]
+    pub zone: Address,
     pub offer: Vec<OfferItem>,
     pub consideration: Vec<ConsiderationItem>,
-    #[serde(rename = "orderType")]
-    pub order_type: u8,
-    #[serde(rename = "startTime")]
-    pub start_time: String,
-    #[serde(rename = "endTime")]
-    pub end_time: String,
-    #[serde(rename = "zoneHash")]
-    pub zone_hash: String,
-    pub salt: String,
-    #[serde(rename = "conduitKey")]
-    pub conduit_key: String,
+    #[serde(rename = "orderType", with = "sol_enum")]
+    pub order_type: seaport::OrderType,
+    #[serde(rename = "startTime", with = "u256_amount")]
+    pub start_time: U256,
+    #[serde(rename = "endTime", with = "u256_amount")]
+    pub end_time: U256,
+    #[serde(rename = "zoneHash", with = "string")]
+    pub zone_hash: B256,
+    #[serde(with = "hex_u256")]
+    pub salt: U256,
+    #[serde(rename = "conduitKey", with = "string")]
+    pub conduit_key: B256,
     #[serde(rename = "totalOriginalConsiderationItems")]
     pub total_original_consideration_items: i16,
+    // the offerer's Seaport counter the order was signed at, when known
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "option_u256_amount"
+    )]
+    pub counter: Option<U256>,
 }
 
 #[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
 pub struct OfferItem {
-    #[serde(rename = "itemType")]
-    pub item_type: u8,
-    pub token: String,
-    #[serde(rename = "identifierOrCriteria")]
-    pub identifier_or_criteria: String,
-    #[serde(rename = "startAmount")]
-    pub start_amount: String,
-    #[serde(rename = "endAmount")]
-    pub end_amount: String,
+    #[serde(rename = "itemType", with = "sol_enum")]
+    pub item_type: seaport::ItemType,
+    #[serde(with = "string")]
+    pub token: Address,
+    #[serde(rename = "identifierOrCriteria", with = "u256_amount")]
+    pub identifier_or_criteria: U256,
+    #[serde(rename = "startAmount", with = "u256_amount")]
+    pub start_amount: U256,
+    #[serde(rename = "endAmount", with = "u256_amount")]
+    pub end_amount: U256,
 }
 
 #[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
 pub struct ConsiderationItem {
-    #[serde(rename = "itemType")]
-    pub item_type: u8,
-    pub token: String,
-    #[serde(rename = "identifierOrCriteria")]
-    pub identifier_or_criteria: String,
-    #[serde(rename = "startAmount")]
-    pub start_amount: String,
-    #[serde(rename = "endAmount")]
-    pub end_amount: String,
-    pub recipient: String,
+    #[serde(rename = "itemType", with = "sol_enum")]
+    pub item_type: seaport::ItemType,
+    #[serde(with = "string")]
+    pub token: Address,
+    #[serde(rename = "identifierOrCriteria", with = "u256_amount")]
+    pub identifier_or_criteria: U256,
+    #[serde(rename = "startAmount", with = "u256_amount")]
+    pub start_amount: U256,
+    #[serde(rename = "endAmount", with = "u256_amount")]
+    pub end_amount: U256,
+    #[serde(with = "string")]
+    pub recipient: Address,
 }
 
 // impl
 
 impl OrderParameters {
-    // initialises the struct with default values
-    pub fn load_default_order_parameters(wallet: String) -> Self {
+    // initialises the struct with default values, for the default chain
+    pub fn load_default_order_parameters(wallet: Address) -> Self {
+        let chain = ChainConfig::default();
         Self {
-            offerer: wallet.clone(),
-            zone: DEFAULT_ORDER_ADDRESS.to_string(),
+            offerer: wallet,
+            zone: chain.zone,
             offer: vec![OfferItem::new(
-                1,
-                "".to_string(),
-                "0".to_string(),
-                "".to_string(),
-                "".to_string(),
+                seaport::ItemType::ERC20,
+                Address::ZERO,
+                U256::ZERO,
+                U256::ZERO,
+                U256::ZERO,
             )],
             consideration: vec![ConsiderationItem::new(
-                1,
-                "".to_string(),
-                "0".to_string(),
-                "".to_string(),
-                "".to_string(),
+                seaport::ItemType::ERC20,
+                Address::ZERO,
+                U256::ZERO,
+                U256::ZERO,
+                U256::ZERO,
                 wallet,
             )],
-            order_type: 3,
-            start_time: "".to_string(),
-            end_time: "".to_string(),
-            zone_hash: "0x0000000000000000000000000000000000000000000000000000000000000000"
-                .to_string(),
-            salt: "0".to_string(),
-            conduit_key: "0x0000000000000000000000000000000000000000000000000000000000000000"
-                .to_string(),
+            order_type: seaport::OrderType::PARTIAL_RESTRICTED,
+            start_time: U256::ZERO,
+            end_time: U256::ZERO,
+            zone_hash: DEFAULT_ZONE_HASH.into(),
+            salt: U256::ZERO,
+            conduit_key: chain.default_conduit_key(),
             total_original_consideration_items: 1,
+            counter: None,
         }
     }
 
-    pub fn to_order_components(self) -> seaport::OrderComponents {
-        seaport::OrderComponents {
-            // offerer: Address::parse_checksummed(&self.offerer, None).unwrap(),
-            offerer: self.offerer.parse::<Address>().unwrap(),
-            zone: self.zone.parse::<Address>().unwrap(),
-            // zone: Address::parse_checksummed(&self.zone, None).unwrap(),
-            offer: self
-                .offer
-                .iter()
-                .map(|item| seaport::OfferItem {
-                    itemType: item.item_type,
-                    token: item.token.parse::<Address>().unwrap(),
-                    // token: Address::parse_checksummed(&item.token, None).unwrap(),
-                    identifierOrCriteria: U256::from(0),
-                    startAmount: U256::from(item.start_amount.parse::<U256>().unwrap()),
-                    endAmount: U256::from(item.end_amount.parse::<U256>().unwrap()),
-                })
-                .collect(),
-            consideration: self
-                .consideration
-                .iter()
-                .map(|item| seaport::ConsiderationItem {
-                    itemType: item.item_type,
-                    // token: Address::parse_checksummed(&item.token, None).unwrap(),
-                    token: item.token.parse::<Address>().unwrap(),
-                    identifierOrCriteria: U256::from(0),
-                    startAmount: U256::from(item.start_amount.parse::<u64>().unwrap()),
-                    endAmount: U256::from(item.end_amount.parse::<u64>().unwrap()),
-                    recipient: item.recipient.parse::<Address>().unwrap(),
-                    // recipient: Address::parse_checksummed(&item.recipient, None).unwrap(),
-                })
-                .collect(),
-            orderType: self.order_type,
-            startTime: U256::from(self.start_time.parse::<U256>().unwrap()),
-            endTime: U256::from(self.end_time.parse::<U256>().unwrap()),
-            zoneHash: DEFAULT_ZONE_HASH.into(),
-            salt: U256::from_str_radix(self.salt.trim_start_matches("0x"), 16).unwrap(),
-            conduitKey: DEFAULT_CONDUIT_KEY.into(),
-            counter: U256::from(0), // @dev: to-do: query seaport for counter here
-        }
+    // points the order at the Aori zone and default conduit of `chain`
+    pub fn with_chain(mut self, chain: &ChainConfig) -> Self {
+        self.zone = chain.zone;
+        self.conduit_key = chain.default_conduit_key();
+        self
+    }
+
+    // restricts the order to `zone`, which is handed `zone_hash` whenever the order is filled
+    pub fn with_zone(mut self, zone: Address, zone_hash: B256) -> Self {
+        self.zone = zone;
+        self.zone_hash = zone_hash;
+        self
+    }
+
+    // sets the offerer counter the order is signed at, which `OrderComponents` needs
+    pub fn with_counter(mut self, counter: U256) -> Self {
+        self.counter = Some(counter);
+        self
+    }
+
+    // the Seaport order hash of these parameters for the given offerer counter
+    pub fn order_hash(&self, counter: U256) -> Result<B256, ConversionError> {
+        let components = seaport::OrderComponents::try_from(self.clone().with_counter(counter))?;
+        Ok(components.order_hash())
     }
 
     // creates a limit order for erc20 to erc20 trade
     pub fn limit_order(
-        wallet: &str,
-        sell_token: &str,
-        sell_amount: &str,
-        buy_token: &str,
-        buy_amount: &str,
+        wallet: Address,
+        sell_token: Address,
+        sell_amount: U256,
+        buy_token: Address,
+        buy_amount: U256,
     ) -> Self {
-        let mut order = Self::load_default_order_parameters(wallet.to_string());
-
-        let start_time = Utc::now().timestamp_millis();
-        let end_time = start_time + 1000 * 60 * 60 * 24; // 24 hours later
-
-        order.start_time = start_time.to_string();
-        order.end_time = end_time.to_string();
-
-        order.offer = vec![OfferItem {
-            item_type: 1,
-            token: sell_token.to_string(),
-            identifier_or_criteria: "0".to_string(), // Replace "criteria" with your criteria
-            start_amount: sell_amount.to_string(),
-            end_amount: sell_amount.to_string(),
-        }];
-
-        order.consideration = vec![ConsiderationItem {
-            item_type: 1,
-            token: buy_token.to_string(),
-            identifier_or_criteria: "0".to_string(), // Replace "criteria" with your criteria
-            start_amount: buy_amount.to_string(),
-            end_amount: buy_amount.to_string(),
-            recipient: wallet.to_string(),
-        }];
+        let mut order = Self::load_default_order_parameters(wallet);
+
+        // seaport times are in seconds
+        let start_time = Utc::now().timestamp() as u64;
+        order.start_time = U256::from(start_time);
+        order.end_time = U256::from(start_time + DEFAULT_DURATION);
+
+        order.offer = vec![OfferItem::new(
+            seaport::ItemType::ERC20,
+            sell_token,
+            U256::ZERO,
+            sell_amount,
+            sell_amount,
+        )];
+        order.consideration = vec![ConsiderationItem::new(
+            seaport::ItemType::ERC20,
+            buy_token,
+            U256::ZERO,
+            buy_amount,
+            buy_amount,
+            wallet,
+        )];
 
         order
     }
+
+    // adds a consideration item, e.g. a payment to someone other than the offerer
+    pub fn with_consideration_item(mut self, item: ConsiderationItem) -> Self {
+        self.consideration.push(item);
+        self.total_original_consideration_items = self.consideration.len() as i16;
+        self
+    }
+
+    // pays `bps` basis points of the offerer's first consideration item to `recipient`,
+    // taking it out of what the offerer receives
+    pub fn with_fee(mut self, recipient: Address, bps: u16) -> eyre::Result<Self> {
+        if bps > MAX_BPS {
+            return Err(eyre::eyre!("fee: {} bps is more than the whole item.", bps));
+        }
+        let offerer = self.offerer;
+        let item = self
+            .consideration
+            .iter_mut()
+            .find(|item| item.recipient == offerer)
+            .ok_or(eyre::eyre!(
+                "fee: no consideration item is paid to the offerer."
+            ))?;
+        let start_fee = bps_of(item.start_amount, bps)?;
+        let end_fee = bps_of(item.end_amount, bps)?;
+        if start_fee.is_zero() || end_fee.is_zero() {
+            return Err(eyre::eyre!(
+                "fee: {} bps of {} rounds down to zero.",
+                bps,
+                item.start_amount.min(item.end_amount)
+            ));
+        }
+
+        item.start_amount -= start_fee;
+        item.end_amount -= end_fee;
+        let fee_item = ConsiderationItem {
+            start_amount: start_fee,
+            end_amount: end_fee,
+            recipient,
+            ..item.clone()
+        };
+        Ok(self.with_consideration_item(fee_item))
+    }
 }
 impl OfferItem {
+    pub fn is_native(&self) -> bool {
+        self.item_type == seaport::ItemType::NATIVE
+    }
+
     pub fn new(
-        item_type: u8,
-        token: String,
-        identifier_or_criteria: String,
-        start_amount: String,
-        end_amount: String,
+        item_type: seaport::ItemType,
+        token: Address,
+        identifier_or_criteria: U256,
+        start_amount: U256,
+        end_amount: U256,
     ) -> Self {
         Self {
             item_type,
@@ -204,13 +249,17 @@ impl OfferItem {
 }
 
 impl ConsiderationItem {
+    pub fn is_native(&self) -> bool {
+        self.item_type == seaport::ItemType::NATIVE
+    }
+
     pub fn new(
-        item_type: u8,
-        token: String,
-        identifier_or_criteria: String,
-        start_amount: String,
-        end_amount: String,
-        recipient: String,
+        item_type: seaport::ItemType,
+        token: Address,
+        identifier_or_criteria: U256,
+        start_amount: U256,
+        end_amount: U256,
+        recipient: Address,
     ) -> Self {
         Self {
             item_type,
@@ -236,21 +285,37 @@ pub enum AoriEvent {
 
     #[serde(rename = "OrderTaken")]
     OrderTaken(Box<OrderTakenData>),
+
+    #[serde(rename = "OrderToSettle")]
+    OrderToSettle(Box<OrderToSettleData>),
+
+    #[serde(rename = "OrderFulfilled")]
+    OrderFulfilled(Box<OrderFulfilledData>),
+
+    #[serde(rename = "QuoteRequested")]
+    QuoteRequested(Box<QuoteRequestedData>),
+
+    // any event type this version doesn't know about, kept as received
+    Unknown {
+        #[serde(rename = "type")]
+        event_type: String,
+        raw: Value,
+    },
 }
 
 #[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
 pub struct OrderCreatedData {
     pub order: OrderCreationData,
-    #[serde(rename = "orderHash")]
-    pub order_hash: String,
-    #[serde(rename = "inputToken")]
-    pub input_token: String,
-    #[serde(rename = "outputToken")]
-    pub output_token: String,
-    #[serde(rename = "inputAmount")]
-    pub input_amount: u64,
-    #[serde(rename = "outputAmount")]
-    pub output_amount: u64,
+    #[serde(rename = "orderHash", with = "string")]
+    pub order_hash: B256,
+    #[serde(rename = "inputToken", with = "string")]
+    pub input_token: Address,
+    #[serde(rename = "outputToken", with = "string")]
+    pub output_token: Address,
+    #[serde(rename = "inputAmount", with = "u256_amount")]
+    pub input_amount: U256,
+    #[serde(rename = "outputAmount", with = "u256_amount")]
+    pub output_amount: U256,
     #[serde(rename = "chainId")]
     pub chain_id: i64,
     pub active: bool,
@@ -266,16 +331,16 @@ pub struct OrderCreatedData {
 #[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
 pub struct OrderCancelledData {
     pub order: OrderCreationData,
-    #[serde(rename = "orderHash")]
-    pub order_hash: String,
-    #[serde(rename = "inputToken")]
-    pub input_token: String,
-    #[serde(rename = "outputToken")]
-    pub output_token: String,
-    #[serde(rename = "inputAmount")]
-    pub input_amount: u64,
-    #[serde(rename = "outputAmount")]
-    pub output_amount: u64,
+    #[serde(rename = "orderHash", with = "string")]
+    pub order_hash: B256,
+    #[serde(rename = "inputToken", with = "string")]
+    pub input_token: Address,
+    #[serde(rename = "outputToken", with = "string")]
+    pub output_token: Address,
+    #[serde(rename = "inputAmount", with = "u256_amount")]
+    pub input_amount: U256,
+    #[serde(rename = "outputAmount", with = "u256_amount")]
+    pub output_amount: U256,
     #[serde(rename = "chainId")]
     pub chain_id: i64,
     pub active: bool,
@@ -290,16 +355,16 @@ pub struct OrderCancelledData {
 #[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
 pub struct OrderTakenData {
     pub order: OrderCreationData,
-    #[serde(rename = "orderHash")]
-    pub order_hash: String,
-    #[serde(rename = "inputToken")]
-    pub input_token: String,
-    #[serde(rename = "outputToken")]
-    pub output_token: String,
-    #[serde(rename = "inputAmount")]
-    pub input_amount: u64,
-    #[serde(rename = "outputAmount")]
-    pub output_amount: u64,
+    #[serde(rename = "orderHash", with = "string")]
+    pub order_hash: B256,
+    #[serde(rename = "inputToken", with = "string")]
+    pub input_token: Address,
+    #[serde(rename = "outputToken", with = "string")]
+    pub output_token: Address,
+    #[serde(rename = "inputAmount", with = "u256_amount")]
+    pub input_amount: U256,
+    #[serde(rename = "outputAmount", with = "u256_amount")]
+    pub output_amount: U256,
     #[serde(rename = "chainId")]
     pub chain_id: i64,
     pub active: bool,
@@ -313,6 +378,134 @@ pub struct OrderTakenData {
     pub taken_at: u64,
 }
 
+// Sent to the matching seat once two orders have been matched and need settling on-chain.
+// Fields the server adds later end up in `extra`.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+pub struct OrderToSettleData {
+    #[serde(rename = "orderHash", with = "string")]
+    pub order_hash: B256,
+    #[serde(default)]
+    pub order: Option<OrderCreationData>,
+    #[serde(rename = "matchingOrder", default)]
+    pub matching_order: Option<OrderCreationData>,
+    #[serde(rename = "chainId", default)]
+    pub chain_id: Option<i64>,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, Value>,
+}
+
+// An order that has been settled on-chain.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+pub struct OrderFulfilledData {
+    #[serde(rename = "orderHash", with = "string")]
+    pub order_hash: B256,
+    #[serde(rename = "inputToken", with = "string")]
+    pub input_token: Address,
+    #[serde(rename = "outputToken", with = "string")]
+    pub output_token: Address,
+    #[serde(rename = "inputAmount", with = "u256_amount")]
+    pub input_amount: U256,
+    #[serde(rename = "outputAmount", with = "u256_amount")]
+    pub output_amount: U256,
+    #[serde(rename = "chainId")]
+    pub chain_id: i64,
+    #[serde(rename = "transactionHash", default, with = "option_string")]
+    pub transaction_hash: Option<B256>,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, Value>,
+}
+
+// Someone is asking market makers to quote `input_amount` of `input_token`.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+pub struct QuoteRequestedData {
+    #[serde(rename = "inputToken", with = "string")]
+    pub input_token: Address,
+    #[serde(rename = "outputToken", with = "string")]
+    pub output_token: Address,
+    #[serde(rename = "inputAmount", with = "u256_amount")]
+    pub input_amount: U256,
+    #[serde(rename = "chainId")]
+    pub chain_id: i64,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, Value>,
+}
+
+impl OrderCreationData {
+    /// Returns whether a server-reported `order_hash` is the Seaport hash of the order's
+    /// parameters, given the offerer's current counter. The order events check their own
+    /// `order_hash` through this.
+    pub fn verify_order_hash(&self, order_hash: B256, counter: U256) -> bool {
+        self.parameters
+            .order_hash(counter)
+            .is_ok_and(|hash| hash == order_hash)
+    }
+}
+
+impl OrderCreatedData {
+    /// Whether the offerer is giving up ETH rather than an ERC20 such as WETH.
+    pub fn is_native_input(&self) -> bool {
+        self.order.parameters.offer.iter().any(OfferItem::is_native)
+    }
+
+    /// Whether the offerer wants ETH rather than an ERC20 such as WETH.
+    pub fn is_native_output(&self) -> bool {
+        let parameters = &self.order.parameters;
+        parameters
+            .consideration
+            .iter()
+            .any(|item| item.is_native() && item.recipient == parameters.offerer)
+    }
+
+    /// The order's input and output amounts at `timestamp`, which differ from `input_amount`
+    /// and `output_amount` while an auction order's price is moving. The input is everything
+    /// offered, the output everything paid back to the offerer.
+    pub fn current_amounts(&self, timestamp: u64) -> eyre::Result<(U256, U256)> {
+        // the counter plays no part in the amounts
+        let components = seaport::OrderComponents::try_from(
+            self.order.parameters.clone().with_counter(U256::ZERO),
+        )?;
+        let input = components
+            .current_offer_amounts(timestamp)?
+            .into_iter()
+            .fold(U256::ZERO, |total, amount| total + amount);
+        let output = components
+            .consideration
+            .iter()
+            .zip(components.current_consideration_amounts(timestamp)?)
+            .filter(|(item, _)| item.recipient == components.offerer)
+            .fold(U256::ZERO, |total, (_, amount)| total + amount);
+        Ok((input, output))
+    }
+
+    pub fn verify_order_hash(&self, counter: U256) -> bool {
+        self.order.verify_order_hash(self.order_hash, counter)
+    }
+}
+
+impl OrderCancelledData {
+    pub fn verify_order_hash(&self, counter: U256) -> bool {
+        self.order.verify_order_hash(self.order_hash, counter)
+    }
+}
+
+impl OrderTakenData {
+    pub fn verify_order_hash(&self, counter: U256) -> bool {
+        self.order.verify_order_hash(self.order_hash, counter)
+    }
+}
+
+// takes an event's payload out of its `data` field
+fn event_data<T, E>(map: &mut serde_json::Map<String, Value>) -> Result<Box<T>, E>
+where
+    T: DeserializeOwned,
+    E: serde::de::Error,
+{
+    let data = map.remove("data").ok_or_else(|| E::missing_field("data"))?;
+    serde_json::from_value(data)
+        .map(Box::new)
+        .map_err(E::custom)
+}
+
 // Implementing Deserialize trait manually for AoriEvent
 impl<'de> Deserialize<'de> for AoriEvent {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
@@ -325,37 +518,263 @@ impl<'de> Deserialize<'de> for AoriEvent {
         let type_ = map
             .get("type")
             .and_then(Value::as_str)
-            .ok_or_else(|| serde::de::Error::missing_field("type"))?;
+            .ok_or_else(|| serde::de::Error::missing_field("type"))?
+            .to_string();
 
         // Handling deserialization based on the type value
-        match type_ {
+        match type_.as_str() {
             "Subscribed to orderbook updates" => {
                 Ok(AoriEvent::Subscribed("Subscribed.".to_string()))
             }
-            "OrderCancelled" => {
-                let data: OrderCancelledData = serde_json::from_value(map.remove("data").unwrap())
-                    .map_err(serde::de::Error::custom)?;
-                Ok(AoriEvent::OrderCancelled(Box::new(data)))
-            }
-            "OrderCreated" => {
-                let data: OrderCreatedData = serde_json::from_value(map.remove("data").unwrap())
-                    .map_err(serde::de::Error::custom)?;
-                Ok(AoriEvent::OrderCreated(Box::new(data)))
-            }
-            "OrderTaken" => {
-                let data: OrderTakenData = serde_json::from_value(map.remove("data").unwrap())
-                    .map_err(serde::de::Error::custom)?;
-                Ok(AoriEvent::OrderTaken(Box::new(data)))
-            }
-            _ => Err(serde::de::Error::unknown_variant(
-                type_,
-                &[
-                    "OrderCancelled",
-                    "OrderCreated",
-                    "OrderTaken",
-                    "Subscribed to orderbook updates",
-                ],
-            )),
+            "OrderCancelled" => event_data(&mut map).map(AoriEvent::OrderCancelled),
+            "OrderCreated" => event_data(&mut map).map(AoriEvent::OrderCreated),
+            "OrderTaken" => event_data(&mut map).map(AoriEvent::OrderTaken),
+            "OrderToSettle" => event_data(&mut map).map(AoriEvent::OrderToSettle),
+            "OrderFulfilled" => event_data(&mut map).map(AoriEvent::OrderFulfilled),
+            "QuoteRequested" => event_data(&mut map).map(AoriEvent::QuoteRequested),
+            _ => Ok(AoriEvent::Unknown {
+                event_type: type_,
+                raw: Value::Object(map),
+            }),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{address, hex};
+
+    const WETH: Address = address!("2715Ccea428F8c7694f7e78B2C89cb454c5F7294");
+    const USDC: Address = address!("D3664B5e72B46eaba722aB6f43c22dBF40181954");
+
+    fn sample_order_created(order_hash: B256) -> OrderCreatedData {
+        let offerer = address!("8f6e1a8a4d0ee1f8e0ff2e6b9b3b8b5d4a79e1c2");
+        let mut parameters = OrderParameters::limit_order(
+            offerer,
+            WETH,
+            U256::from(1000000000000000000_u64),
+            USDC,
+            U256::from(1500000000),
+        );
+        parameters.start_time = U256::from(1697240202);
+        parameters.end_time = U256::from(1697326602);
+        parameters.salt = U256::from(42);
+
+        OrderCreatedData {
+            order: OrderCreationData {
+                parameters,
+                signature: "0x".to_string(),
+            },
+            order_hash,
+            input_token: WETH,
+            output_token: USDC,
+            input_amount: U256::from(1000000000000000000_u64),
+            output_amount: U256::from(1500000000),
+            chain_id: 5,
+            active: true,
+            created_at: 1697240202,
+            last_updated_at: 1697240202,
+            is_public: true,
+            rate: None,
+        }
+    }
+
+    // the sample order with the hash it has when signed at counter 3
+    fn signed_sample_order() -> OrderCreatedData {
+        let order = sample_order_created(B256::ZERO);
+        let order_hash = order.order.parameters.order_hash(U256::from(3)).unwrap();
+        sample_order_created(order_hash)
+    }
+
+    #[test]
+    fn verify_order_hash() {
+        let order = signed_sample_order();
+        assert!(order.verify_order_hash(U256::from(3)));
+        assert!(!order.verify_order_hash(U256::ZERO));
+
+        let tampered = sample_order_created(B256::with_last_byte(1));
+        assert!(!tampered.verify_order_hash(U256::from(3)));
+
+        // a malformed hash is rejected when the event is parsed
+        let mut json = serde_json::to_value(&order).unwrap();
+        json["orderHash"] = "not a hash".into();
+        assert!(serde_json::from_value::<OrderCreatedData>(json).is_err());
+    }
+
+    #[test]
+    fn known_events() {
+        let order = sample_order_created(B256::with_last_byte(1));
+        let event: AoriEvent = serde_json::from_value(serde_json::json!({
+            "type": "OrderCreated",
+            "data": order,
+        }))
+        .unwrap();
+        assert_eq!(event, AoriEvent::OrderCreated(Box::new(order)));
+
+        let event: AoriEvent = serde_json::from_value(serde_json::json!({
+            "type": "OrderToSettle",
+            "data": {
+                "orderHash": B256::with_last_byte(1).to_string(),
+                "chainId": 5,
+                "seatId": 3,
+            },
+        }))
+        .unwrap();
+        let AoriEvent::OrderToSettle(data) = event else {
+            panic!("expected OrderToSettle, got {:?}", event);
+        };
+        assert_eq!(data.order_hash, B256::with_last_byte(1));
+        assert_eq!(data.chain_id, Some(5));
+        assert!(data.order.is_none());
+        assert_eq!(data.extra["seatId"], 3);
+
+        let event: AoriEvent = serde_json::from_value(serde_json::json!({
+            "type": "QuoteRequested",
+            "data": {
+                "inputToken": "0x2715Ccea428F8c7694f7e78B2C89cb454c5F7294",
+                "outputToken": "0xD3664B5e72B46eaba722aB6f43c22dBF40181954",
+                "inputAmount": "1000000000000000000",
+                "chainId": 5,
+            },
+        }))
+        .unwrap();
+        assert!(matches!(event, AoriEvent::QuoteRequested(_)));
+    }
+
+    #[test]
+    fn unknown_and_malformed_events() {
+        let raw = serde_json::json!({
+            "type": "SeatAuctionStarted",
+            "data": { "seatId": 3 },
+        });
+        let event: AoriEvent = serde_json::from_value(raw.clone()).unwrap();
+        assert_eq!(
+            event,
+            AoriEvent::Unknown {
+                event_type: "SeatAuctionStarted".to_string(),
+                raw,
+            }
+        );
+
+        // a known type without its payload is an error, not a panic
+        let err = serde_json::from_value::<AoriEvent>(serde_json::json!({
+            "type": "OrderCreated",
+        }))
+        .unwrap_err();
+        assert!(err.to_string().contains("missing field `data`"));
+    }
+
+    #[test]
+    fn amounts_past_u64() {
+        let order = sample_order_created(B256::ZERO);
+        let mut json = serde_json::to_value(&order).unwrap();
+        assert_eq!(json["inputAmount"], "1000000000000000000");
+
+        // 100 WETH is fine as a string, but can't be trusted as a bare number
+        json["inputAmount"] = "100000000000000000000".into();
+        let parsed: OrderCreatedData = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(parsed.input_amount, U256::from(100000000000000000000_u128));
+        let raw = json
+            .to_string()
+            .replace("\"100000000000000000000\"", "100000000000000000000");
+        assert!(serde_json::from_str::<OrderCreatedData>(&raw).is_err());
+        // numbers still parse as before
+        json["outputAmount"] = 1500000000.into();
+        let parsed: OrderCreatedData = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.output_amount, order.output_amount);
+    }
+
+    #[test]
+    fn limit_order_with_fee() {
+        let order = sample_order_created(B256::ZERO).order.parameters;
+        let protocol = address!("70997970C51812dc3A010C7d01b50e0d17dc79C8");
+        let order = order.with_fee(protocol, 30).unwrap();
+
+        assert_eq!(order.total_original_consideration_items, 2);
+        assert_eq!(order.consideration[0].start_amount, U256::from(1495500000));
+        assert_eq!(order.consideration[1].start_amount, U256::from(4500000));
+        assert_eq!(order.consideration[1].recipient, protocol);
+
+        let components =
+            seaport::OrderComponents::try_from(order.with_counter(U256::ZERO)).unwrap();
+        assert_eq!(components.consideration.len(), 2);
+        assert_eq!(
+            components
+                .to_order_parameters()
+                .unwrap()
+                .totalOriginalConsiderationItems,
+            U256::from(2)
+        );
+        assert!(sample_order_created(B256::ZERO)
+            .order
+            .parameters
+            .with_fee(protocol, MAX_BPS + 1)
+            .is_err());
+    }
+
+    #[test]
+    fn custom_zone_hash_is_kept() {
+        let zone = address!("3C44CdDdB6a900fa2b585dd299e03d12FA4293BC");
+        let zone_hash = B256::repeat_byte(0x11);
+        let parameters = sample_order_created(B256::ZERO)
+            .order
+            .parameters
+            .with_zone(zone, zone_hash)
+            .with_counter(U256::ZERO);
+
+        let json = serde_json::to_value(&parameters).unwrap();
+        let parsed: OrderParameters = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.zone_hash, zone_hash);
+
+        let components = seaport::OrderComponents::try_from(parsed).unwrap();
+        assert_eq!((components.zone, components.zoneHash), (zone, zone_hash));
+        assert_eq!(
+            components.to_order_parameters().unwrap().zoneHash,
+            zone_hash
+        );
+        assert_ne!(
+            parameters.order_hash(U256::ZERO).unwrap(),
+            parameters
+                .with_zone(zone, B256::ZERO)
+                .order_hash(U256::ZERO)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn native_legs() {
+        let mut order = sample_order_created(B256::ZERO);
+        assert!(!order.is_native_input());
+        assert!(!order.is_native_output());
+
+        let consideration = &mut order.order.parameters.consideration[0];
+        consideration.item_type = seaport::ItemType::NATIVE;
+        consideration.token = Address::ZERO;
+        assert!(order.is_native_output());
+        let components =
+            seaport::OrderComponents::try_from(order.order.parameters.with_counter(U256::ZERO))
+                .unwrap();
+        assert!(components.consideration[0].is_native());
+    }
+
+    #[test]
+    fn fields_parse_in_any_casing() {
+        let order = signed_sample_order();
+        let mut json = serde_json::to_value(&order).unwrap();
+        assert_eq!(
+            json["inputToken"],
+            "0x2715Ccea428F8c7694f7e78B2C89cb454c5F7294"
+        );
+        json["inputToken"] = "0x2715ccea428f8c7694f7e78b2c89cb454c5f7294".into();
+        json["orderHash"] = format!("0x{}", hex::encode_upper(order.order_hash)).into();
+        json["order"]["parameters"]["offerer"] =
+            "0x8F6E1A8A4D0EE1F8E0FF2E6B9B3B8B5D4A79E1C2".into();
+        json["order"]["parameters"]["salt"] = "2a".into();
+        json["order"]["parameters"]["offer"][0]["identifierOrCriteria"] = "0x0".into();
+
+        let parsed: OrderCreatedData = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, order);
+        assert!(parsed.verify_order_hash(U256::from(3)));
+    }
+}
@@ -0,0 +1,144 @@
+use alloy_primitives::{Address, U256};
+
+use crate::seaport::{ConsiderationItem, ItemType};
+
+/// Basis points in a whole: 10_000 bps is 100%.
+pub const MAX_BPS: u16 = 10_000;
+
+/// `bps` basis points of `amount`, rounded down. Fails if `amount * bps` overflows a uint256.
+pub fn bps_of(amount: U256, bps: u16) -> eyre::Result<U256> {
+    let scaled = amount.checked_mul(U256::from(bps)).ok_or(eyre::eyre!(
+        "fee: {} bps of {} overflows a uint256.",
+        bps,
+        amount
+    ))?;
+    Ok(scaled / U256::from(MAX_BPS))
+}
+
+/// A cut of an order's proceeds paid to someone other than the offerer, such as a protocol
+/// or referral fee.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Fee {
+    pub recipient: Address,
+    pub bps: u16,
+}
+
+impl Fee {
+    pub fn new(recipient: Address, bps: u16) -> Self {
+        Self { recipient, bps }
+    }
+}
+
+/// Splits `fees` out of `item`, returning the item with its amounts reduced followed by one
+/// item per fee in the same token. Fees are taken from the start and end amounts separately,
+/// so they follow the item along any auction curve.
+pub fn split_consideration(
+    item: &ConsiderationItem,
+    fees: &[Fee],
+) -> eyre::Result<Vec<ConsiderationItem>> {
+    if !matches!(
+        ItemType::try_from(item.itemType),
+        Ok(ItemType::NATIVE | ItemType::ERC20 | ItemType::ERC1155)
+    ) {
+        return Err(eyre::eyre!(
+            "fee: cannot take a fee from item type {}.",
+            item.itemType
+        ));
+    }
+    let total_bps: u32 = fees.iter().map(|fee| fee.bps as u32).sum();
+    if total_bps > MAX_BPS as u32 {
+        return Err(eyre::eyre!(
+            "fee: {} bps in total is more than the whole item.",
+            total_bps
+        ));
+    }
+
+    let mut remainder = item.clone();
+    let mut items = Vec::with_capacity(fees.len() + 1);
+    for fee in fees {
+        let start_amount = bps_of(item.startAmount, fee.bps)?;
+        let end_amount = bps_of(item.endAmount, fee.bps)?;
+        if start_amount.is_zero() || end_amount.is_zero() {
+            return Err(eyre::eyre!(
+                "fee: {} bps of {} rounds down to zero.",
+                fee.bps,
+                item.startAmount.min(item.endAmount)
+            ));
+        }
+        remainder.startAmount -= start_amount;
+        remainder.endAmount -= end_amount;
+        items.push(ConsiderationItem {
+            startAmount: start_amount,
+            endAmount: end_amount,
+            recipient: fee.recipient,
+            ..item.clone()
+        });
+    }
+    items.insert(0, remainder);
+    Ok(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+
+    const OFFERER: Address = address!("f39Fd6e51aad88F6F4ce6aB8827279cffFb92266");
+    const PROTOCOL: Address = address!("70997970C51812dc3A010C7d01b50e0d17dc79C8");
+    const REFERRER: Address = address!("3C44CdDdB6a900fa2b585dd299e03d12FA4293BC");
+    const USDC: Address = address!("D3664B5e72B46eaba722aB6f43c22dBF40181954");
+
+    fn item(start_amount: u64, end_amount: u64) -> ConsiderationItem {
+        ConsiderationItem {
+            itemType: ItemType::ERC20 as u8,
+            token: USDC,
+            identifierOrCriteria: U256::ZERO,
+            startAmount: U256::from(start_amount),
+            endAmount: U256::from(end_amount),
+            recipient: OFFERER,
+        }
+    }
+
+    #[test]
+    fn bps() {
+        assert_eq!(
+            bps_of(U256::from(1500000000), 30).unwrap(),
+            U256::from(4500000)
+        );
+        assert_eq!(bps_of(U256::from(999), 1).unwrap(), U256::ZERO);
+        assert_eq!(bps_of(U256::from(999), MAX_BPS).unwrap(), U256::from(999));
+        assert!(bps_of(U256::MAX, 2).is_err());
+    }
+
+    #[test]
+    fn splits_fees_out_of_the_item() {
+        let fees = [Fee::new(PROTOCOL, 30), Fee::new(REFERRER, 10)];
+        let items = split_consideration(&item(1500000000, 1000000000), &fees).unwrap();
+
+        assert_eq!(items.len(), 3);
+        assert_eq!(items[0].recipient, OFFERER);
+        assert_eq!(items[0].startAmount, U256::from(1494000000));
+        assert_eq!(items[0].endAmount, U256::from(996000000));
+        assert_eq!(items[1].recipient, PROTOCOL);
+        assert_eq!(items[1].startAmount, U256::from(4500000));
+        assert_eq!(items[1].endAmount, U256::from(3000000));
+        assert_eq!(items[2].recipient, REFERRER);
+        assert_eq!(items[2].token, USDC);
+
+        let total: U256 = items.iter().map(|item| item.startAmount).sum();
+        assert_eq!(total, U256::from(1500000000));
+    }
+
+    #[test]
+    fn rejects_invalid_fees() {
+        assert!(split_consideration(&item(100, 100), &[Fee::new(PROTOCOL, MAX_BPS + 1)]).is_err());
+        assert!(split_consideration(&item(100, 100), &[Fee::new(PROTOCOL, 1)]).is_err());
+        let mut large = item(100, 100);
+        large.endAmount = U256::MAX;
+        assert!(split_consideration(&large, &[Fee::new(PROTOCOL, 100)]).is_err());
+
+        let mut nft = item(1, 1);
+        nft.itemType = ItemType::ERC721 as u8;
+        assert!(split_consideration(&nft, &[Fee::new(PROTOCOL, 100)]).is_err());
+    }
+}
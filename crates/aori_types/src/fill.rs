@@ -0,0 +1,374 @@
+use alloy_primitives::U256;
+
+use crate::{
+    auction::current_amount,
+    seaport::{OrderComponents, OrderType},
+};
+
+// Seaport stores fill fractions as uint120s
+const MAX_UINT120: u128 = u128::MAX >> 8;
+
+/// How much of an order to take.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FillAmount {
+    /// The whole order.
+    Full,
+    /// `numerator / denominator` of the order.
+    Fraction { numerator: U256, denominator: U256 },
+    /// Exactly this much of the order's first offer item. The item can't be an auction, as
+    /// its amount would move before the fill lands.
+    OfferAmount(U256),
+    /// Exactly this much of the order's first consideration item. The item can't be an
+    /// auction either.
+    ConsiderationAmount(U256),
+}
+
+impl FillAmount {
+    /// Resolves the fill into the reduced `(numerator, denominator)` pair Seaport expects
+    /// for an `AdvancedOrder`.
+    ///
+    /// Seaport reverts on fractions that don't divide every item amount exactly, so this
+    /// rejects those up front rather than letting the fill fail on-chain.
+    pub fn to_fraction(&self, order: &OrderComponents) -> eyre::Result<(u128, u128)> {
+        let (numerator, denominator) = match *self {
+            FillAmount::Full => return Ok((1, 1)),
+            FillAmount::Fraction {
+                numerator,
+                denominator,
+            } => (numerator, denominator),
+            FillAmount::OfferAmount(amount) => {
+                let item = order
+                    .offer
+                    .first()
+                    .ok_or(eyre::eyre!("fill: order has no offer items."))?;
+                (amount, fixed_amount(item.startAmount, item.endAmount)?)
+            }
+            FillAmount::ConsiderationAmount(amount) => {
+                let item = order
+                    .consideration
+                    .first()
+                    .ok_or(eyre::eyre!("fill: order has no consideration items."))?;
+                (amount, fixed_amount(item.startAmount, item.endAmount)?)
+            }
+        };
+
+        if numerator.is_zero() || denominator.is_zero() {
+            return Err(eyre::eyre!("fill: fraction must be non-zero."));
+        }
+        if numerator > denominator {
+            return Err(eyre::eyre!(
+                "fill: cannot take more than the whole order ({}/{}).",
+                numerator,
+                denominator
+            ));
+        }
+
+        let divisor = gcd(numerator, denominator);
+        let (numerator, denominator) = (numerator / divisor, denominator / divisor);
+
+        if numerator != denominator && !is_partial(order.orderType) {
+            return Err(eyre::eyre!(
+                "fill: order type {} does not allow partial fills.",
+                order.orderType
+            ));
+        }
+
+        let amounts = order
+            .offer
+            .iter()
+            .flat_map(|item| [item.startAmount, item.endAmount])
+            .chain(
+                order
+                    .consideration
+                    .iter()
+                    .flat_map(|item| [item.startAmount, item.endAmount]),
+            );
+        for amount in amounts {
+            if !(checked_mul(amount, numerator)? % denominator).is_zero() {
+                return Err(eyre::eyre!(
+                    "fill: {}/{} does not divide item amount {} exactly.",
+                    numerator,
+                    denominator,
+                    amount
+                ));
+            }
+        }
+
+        Ok((to_uint120(numerator)?, to_uint120(denominator)?))
+    }
+}
+
+impl OrderComponents {
+    /// The ETH a fulfiller has to send as `msg.value` to take `numerator / denominator` of
+    /// the order at `timestamp`, applying the fraction before the auction curve as Seaport does.
+    pub fn native_value(
+        &self,
+        numerator: u128,
+        denominator: u128,
+        timestamp: u64,
+    ) -> eyre::Result<U256> {
+        let mut total = U256::ZERO;
+        for item in self.consideration.iter().filter(|item| item.is_native()) {
+            total += current_amount(
+                fraction_of(item.startAmount, numerator, denominator)?,
+                fraction_of(item.endAmount, numerator, denominator)?,
+                self.startTime,
+                self.endTime,
+                timestamp,
+                true,
+            )?;
+        }
+        Ok(total)
+    }
+
+    /// What each offer item pays out when `numerator / denominator` of the order is taken at
+    /// `timestamp`, rounded down as Seaport does for offer items.
+    pub fn offer_amounts(
+        &self,
+        numerator: u128,
+        denominator: u128,
+        timestamp: u64,
+    ) -> eyre::Result<Vec<U256>> {
+        self.offer
+            .iter()
+            .map(|item| {
+                current_amount(
+                    fraction_of(item.startAmount, numerator, denominator)?,
+                    fraction_of(item.endAmount, numerator, denominator)?,
+                    self.startTime,
+                    self.endTime,
+                    timestamp,
+                    false,
+                )
+            })
+            .collect()
+    }
+
+    /// The most ETH taking the order can require from `timestamp` until it expires. Sending
+    /// this much can't fall short by the time the transaction lands, and Seaport refunds the
+    /// excess.
+    pub fn max_native_value(
+        &self,
+        numerator: u128,
+        denominator: u128,
+        timestamp: u64,
+    ) -> eyre::Result<U256> {
+        // auction amounts move monotonically, so the maximum sits at one of the ends
+        let end_time = self.endTime.saturating_to::<u64>();
+        Ok(self
+            .native_value(numerator, denominator, timestamp)?
+            .max(self.native_value(numerator, denominator, end_time)?))
+    }
+}
+
+fn is_partial(order_type: u8) -> bool {
+    order_type == OrderType::PARTIAL_OPEN as u8 || order_type == OrderType::PARTIAL_RESTRICTED as u8
+}
+
+// the amount an exact fill is measured against, which only exists when the item isn't an auction
+fn fixed_amount(start_amount: U256, end_amount: U256) -> eyre::Result<U256> {
+    if start_amount != end_amount {
+        return Err(eyre::eyre!(
+            "fill: exact amounts can't be taken from an auction item ({} to {}); use a fraction.",
+            start_amount,
+            end_amount
+        ));
+    }
+    Ok(start_amount)
+}
+
+// Seaport reverts when scaling an item amount by the fill fraction overflows
+fn checked_mul(amount: U256, numerator: U256) -> eyre::Result<U256> {
+    amount.checked_mul(numerator).ok_or(eyre::eyre!(
+        "fill: {} times {} overflows a uint256.",
+        amount,
+        numerator
+    ))
+}
+
+// an item amount scaled to a fill fraction, before the auction curve is applied
+pub(crate) fn fraction_of(amount: U256, numerator: u128, denominator: u128) -> eyre::Result<U256> {
+    Ok(checked_mul(amount, U256::from(numerator))? / U256::from(denominator))
+}
+
+// `fraction_of`, rejecting a fraction that leaves a remainder as Seaport does instead of
+// rounding it away
+pub(crate) fn exact_fraction_of(
+    amount: U256,
+    numerator: u128,
+    denominator: u128,
+) -> eyre::Result<U256> {
+    if !(checked_mul(amount, U256::from(numerator))? % U256::from(denominator)).is_zero() {
+        return Err(eyre::eyre!(
+            "fill: {}/{} does not divide item amount {} exactly.",
+            numerator,
+            denominator,
+            amount
+        ));
+    }
+    fraction_of(amount, numerator, denominator)
+}
+
+fn gcd(mut a: U256, mut b: U256) -> U256 {
+    while !b.is_zero() {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+fn to_uint120(value: U256) -> eyre::Result<u128> {
+    if value > U256::from(MAX_UINT120) {
+        return Err(eyre::eyre!("fill: {} does not fit in a uint120.", value));
+    }
+    Ok(value.to::<u128>())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::seaport::{ConsiderationItem, ItemType, OfferItem};
+    use alloy_primitives::{Address, B256};
+
+    fn order(
+        order_type: OrderType,
+        offer_amount: u64,
+        consideration_amount: u64,
+    ) -> OrderComponents {
+        OrderComponents {
+            offerer: Address::ZERO,
+            zone: Address::ZERO,
+            offer: vec![OfferItem {
+                itemType: ItemType::ERC20 as u8,
+                token: Address::ZERO,
+                identifierOrCriteria: U256::ZERO,
+                startAmount: U256::from(offer_amount),
+                endAmount: U256::from(offer_amount),
+            }],
+            consideration: vec![ConsiderationItem {
+                itemType: ItemType::ERC20 as u8,
+                token: Address::ZERO,
+                identifierOrCriteria: U256::ZERO,
+                startAmount: U256::from(consideration_amount),
+                endAmount: U256::from(consideration_amount),
+                recipient: Address::ZERO,
+            }],
+            orderType: order_type as u8,
+            startTime: U256::ZERO,
+            endTime: U256::from(1),
+            zoneHash: B256::ZERO,
+            salt: U256::ZERO,
+            conduitKey: B256::ZERO,
+            counter: U256::ZERO,
+        }
+    }
+
+    #[test]
+    fn native_value() {
+        let mut order = order(OrderType::PARTIAL_RESTRICTED, 100, 300);
+        assert_eq!(order.native_value(1, 1, 0).unwrap(), U256::ZERO);
+
+        order.consideration[0].itemType = ItemType::NATIVE as u8;
+        assert_eq!(order.native_value(1, 1, 0).unwrap(), U256::from(300));
+        assert_eq!(order.native_value(1, 3, 0).unwrap(), U256::from(100));
+
+        // ascending consideration: the most is owed at the end
+        order.endTime = U256::from(100);
+        order.consideration[0].endAmount = U256::from(600);
+        assert_eq!(order.native_value(1, 3, 50).unwrap(), U256::from(150));
+        assert_eq!(order.max_native_value(1, 3, 50).unwrap(), U256::from(200));
+
+        order.consideration[0].endAmount = U256::MAX;
+        assert!(order.native_value(2, 3, 50).is_err());
+
+        // offer items round down where consideration items round up
+        order.offer[0].endAmount = U256::from(50);
+        assert_eq!(order.offer_amounts(1, 3, 50).unwrap(), vec![U256::from(24)]);
+    }
+
+    #[test]
+    fn full_fill() {
+        let order = order(OrderType::FULL_RESTRICTED, 100, 300);
+        assert_eq!(FillAmount::Full.to_fraction(&order).unwrap(), (1, 1));
+    }
+
+    #[test]
+    fn fraction_is_reduced() {
+        let order = order(OrderType::PARTIAL_RESTRICTED, 100, 300);
+        let fill = FillAmount::Fraction {
+            numerator: U256::from(25),
+            denominator: U256::from(100),
+        };
+        assert_eq!(fill.to_fraction(&order).unwrap(), (1, 4));
+    }
+
+    #[test]
+    fn exact_amounts() {
+        let order = order(OrderType::PARTIAL_RESTRICTED, 100, 300);
+        assert_eq!(
+            FillAmount::OfferAmount(U256::from(40))
+                .to_fraction(&order)
+                .unwrap(),
+            (2, 5)
+        );
+        assert_eq!(
+            FillAmount::ConsiderationAmount(U256::from(150))
+                .to_fraction(&order)
+                .unwrap(),
+            (1, 2)
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_fills() {
+        let partial = order(OrderType::PARTIAL_RESTRICTED, 100, 300);
+        // 1/7 of 100 isn't a whole amount
+        assert!(FillAmount::Fraction {
+            numerator: U256::from(1),
+            denominator: U256::from(7),
+        }
+        .to_fraction(&partial)
+        .is_err());
+        assert!(FillAmount::OfferAmount(U256::from(101))
+            .to_fraction(&partial)
+            .is_err());
+        assert!(FillAmount::OfferAmount(U256::ZERO)
+            .to_fraction(&partial)
+            .is_err());
+
+        let full_only = order(OrderType::FULL_RESTRICTED, 100, 300);
+        assert!(FillAmount::OfferAmount(U256::from(50))
+            .to_fraction(&full_only)
+            .is_err());
+        assert!(FillAmount::OfferAmount(U256::from(100))
+            .to_fraction(&full_only)
+            .is_ok());
+
+        // a fraction of uint256-sized amounts can't be scaled
+        let mut huge = order(OrderType::PARTIAL_RESTRICTED, 100, 300);
+        huge.offer[0].startAmount = U256::MAX - U256::from(1);
+        huge.offer[0].endAmount = U256::MAX - U256::from(1);
+        assert!(FillAmount::Fraction {
+            numerator: U256::from(2),
+            denominator: U256::from(3),
+        }
+        .to_fraction(&huge)
+        .is_err());
+    }
+
+    #[test]
+    fn exact_amounts_need_fixed_items() {
+        let mut auction = order(OrderType::PARTIAL_RESTRICTED, 100, 300);
+        auction.offer[0].endAmount = U256::from(50);
+        let err = FillAmount::OfferAmount(U256::from(50))
+            .to_fraction(&auction)
+            .unwrap_err();
+        assert!(err.to_string().contains("auction item (100 to 50)"));
+        // the consideration item still has a fixed amount
+        assert_eq!(
+            FillAmount::ConsiderationAmount(U256::from(150))
+                .to_fraction(&auction)
+                .unwrap(),
+            (1, 2)
+        );
+    }
+}
@@ -0,0 +1,413 @@
+use std::{collections::HashMap, fmt};
+
+use alloy_primitives::{Address, B256, U256};
+
+use crate::{
+    chains::ChainConfig,
+    fill::FillAmount,
+    seaport::{ItemType, OrderComponents},
+};
+
+/// What the offerer holds of an ERC20 and lets Seaport (or its conduit) move. Either is
+/// `None` if it couldn't be read, e.g. because the token's call reverted.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TokenFunds {
+    pub balance: Option<U256>,
+    pub allowance: Option<U256>,
+}
+
+/// Why taking an order would revert on-chain.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Unfillable {
+    NotStarted {
+        start_time: U256,
+    },
+    Expired {
+        end_time: U256,
+    },
+    /// The offerer has bumped its counter since signing, which cancels the order.
+    StaleCounter {
+        signed_at: U256,
+        current: U256,
+    },
+    InsufficientBalance {
+        token: Address,
+        needed: U256,
+        balance: U256,
+    },
+    InsufficientAllowance {
+        token: Address,
+        spender: Address,
+        needed: U256,
+        allowance: U256,
+    },
+    /// The offerer's `balanceOf` or `allowance` of an offered ERC20 couldn't be read.
+    UnreadableFunds {
+        token: Address,
+        call: &'static str,
+    },
+    /// An offer item whose funds this check can't confirm, such as ETH or an NFT. The order
+    /// may well be fillable, but it isn't known to be.
+    Unchecked {
+        item_type: ItemType,
+        token: Address,
+    },
+}
+
+impl fmt::Display for Unfillable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotStarted { start_time } => write!(f, "starts at {}", start_time),
+            Self::Expired { end_time } => write!(f, "expired at {}", end_time),
+            Self::StaleCounter { signed_at, current } => {
+                write!(f, "signed at counter {}, now {}", signed_at, current)
+            }
+            Self::InsufficientBalance {
+                token,
+                needed,
+                balance,
+            } => write!(f, "needs {} of {}, holds {}", needed, token, balance),
+            Self::InsufficientAllowance {
+                token,
+                spender,
+                needed,
+                allowance,
+            } => write!(
+                f,
+                "needs {} of {} approved to {}, has {}",
+                needed, token, spender, allowance
+            ),
+            Self::UnreadableFunds { token, call } => {
+                write!(f, "cannot read {} of {}", call, token)
+            }
+            Self::Unchecked { item_type, token } => {
+                write!(f, "cannot check funds of {:?} item {}", item_type, token)
+            }
+        }
+    }
+}
+
+/// Whether an order can be taken as it stands, and if not, everything in the way.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FillabilityVerdict {
+    pub order_hash: B256,
+    pub problems: Vec<Unfillable>,
+}
+
+impl FillabilityVerdict {
+    /// Checks taking `fill` of `order` at `timestamp` against the offerer's `current_counter`
+    /// and `funds` for each ERC20 it offers. Tokens missing from `funds` count as unreadable.
+    ///
+    /// Only ERC20 offer items can be checked for funds; any other offer item is reported as
+    /// `Unfillable::Unchecked`.
+    pub fn assess(
+        order: &OrderComponents,
+        chain: &ChainConfig,
+        fill: &FillAmount,
+        timestamp: u64,
+        current_counter: U256,
+        funds: &HashMap<Address, TokenFunds>,
+    ) -> eyre::Result<Self> {
+        let mut problems = Vec::new();
+        let now = U256::from(timestamp);
+        if now < order.startTime {
+            problems.push(Unfillable::NotStarted {
+                start_time: order.startTime,
+            });
+        }
+        if now >= order.endTime {
+            problems.push(Unfillable::Expired {
+                end_time: order.endTime,
+            });
+        }
+        if order.counter != current_counter {
+            problems.push(Unfillable::StaleCounter {
+                signed_at: order.counter,
+                current: current_counter,
+            });
+        }
+
+        for item in order
+            .offer
+            .iter()
+            .filter(|item| item.itemType != ItemType::ERC20 as u8)
+        {
+            problems.push(Unfillable::Unchecked {
+                item_type: ItemType::try_from(item.itemType).map_err(|_| {
+                    eyre::eyre!("fillability: unknown item type {}.", item.itemType)
+                })?,
+                token: item.token,
+            });
+        }
+
+        let (numerator, denominator) = fill.to_fraction(order)?;
+        let spender = chain.approval_target(order.conduitKey);
+        for (token, needed) in erc20_offer_amounts(order, numerator, denominator, timestamp)? {
+            let held = funds.get(&token).copied().unwrap_or_default();
+            match held.balance {
+                None => problems.push(Unfillable::UnreadableFunds {
+                    token,
+                    call: "balanceOf",
+                }),
+                Some(balance) if balance < needed => {
+                    problems.push(Unfillable::InsufficientBalance {
+                        token,
+                        needed,
+                        balance,
+                    })
+                }
+                Some(_) => {}
+            }
+            match held.allowance {
+                None => problems.push(Unfillable::UnreadableFunds {
+                    token,
+                    call: "allowance",
+                }),
+                Some(allowance) if allowance < needed => {
+                    problems.push(Unfillable::InsufficientAllowance {
+                        token,
+                        spender,
+                        needed,
+                        allowance,
+                    })
+                }
+                Some(_) => {}
+            }
+        }
+
+        Ok(Self {
+            order_hash: order.order_hash(),
+            problems,
+        })
+    }
+
+    pub fn is_fillable(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+/// The ERC20s `order` offers and how much of each it pays out when `numerator / denominator`
+/// of it is taken at `timestamp`, in the order they first appear. Items of the same token are
+/// added up.
+pub fn erc20_offer_amounts(
+    order: &OrderComponents,
+    numerator: u128,
+    denominator: u128,
+    timestamp: u64,
+) -> eyre::Result<Vec<(Address, U256)>> {
+    let mut amounts: Vec<(Address, U256)> = Vec::new();
+    for (item, amount) in
+        order
+            .offer
+            .iter()
+            .zip(order.offer_amounts(numerator, denominator, timestamp)?)
+    {
+        if item.itemType != ItemType::ERC20 as u8 {
+            continue;
+        }
+        match amounts.iter_mut().find(|(token, _)| *token == item.token) {
+            Some((_, total)) => *total += amount,
+            None => amounts.push((item.token, amount)),
+        }
+    }
+    Ok(amounts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::order_builder::OrderBuilder;
+    use alloy_primitives::{address, b256};
+
+    const OFFERER: Address = address!("f39Fd6e51aad88F6F4ce6aB8827279cffFb92266");
+    const WETH: Address = address!("2715Ccea428F8c7694f7e78B2C89cb454c5F7294");
+    const USDC: Address = address!("D3664B5e72B46eaba722aB6f43c22dBF40181954");
+    const NOW: u64 = 1697240202;
+
+    fn order() -> OrderComponents {
+        OrderBuilder::new(OFFERER)
+            .offer_erc20(WETH, U256::from(600))
+            .offer_erc20(WETH, U256::from(400))
+            .consideration_erc20(USDC, U256::from(1500))
+            .start_time(NOW)
+            .duration(600)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn fillable_with_funds_and_allowance() {
+        let order = order();
+        assert_eq!(
+            erc20_offer_amounts(&order, 1, 1, NOW).unwrap(),
+            vec![(WETH, U256::from(1000))]
+        );
+        let funds = HashMap::from([(
+            WETH,
+            TokenFunds {
+                balance: Some(U256::from(1000)),
+                allowance: Some(U256::MAX),
+            },
+        )]);
+        let verdict = FillabilityVerdict::assess(
+            &order,
+            &ChainConfig::goerli(),
+            &FillAmount::Full,
+            NOW,
+            U256::ZERO,
+            &funds,
+        )
+        .unwrap();
+        assert!(verdict.is_fillable());
+        assert_eq!(verdict.order_hash, order.order_hash());
+    }
+
+    #[test]
+    fn partial_fills_need_only_their_share() {
+        let order = order();
+        let funds = HashMap::from([(
+            WETH,
+            TokenFunds {
+                balance: Some(U256::from(500)),
+                allowance: Some(U256::from(500)),
+            },
+        )]);
+        let check = |fill: FillAmount| {
+            FillabilityVerdict::assess(
+                &order,
+                &ChainConfig::goerli(),
+                &fill,
+                NOW,
+                U256::ZERO,
+                &funds,
+            )
+            .unwrap()
+        };
+        assert!(check(FillAmount::Fraction {
+            numerator: U256::from(1),
+            denominator: U256::from(2),
+        })
+        .is_fillable());
+        assert!(!check(FillAmount::Full).is_fillable());
+    }
+
+    #[test]
+    fn unreadable_funds_are_reported() {
+        let order = order();
+        let funds = HashMap::from([(
+            WETH,
+            TokenFunds {
+                balance: Some(U256::from(1000)),
+                allowance: None,
+            },
+        )]);
+        let verdict = FillabilityVerdict::assess(
+            &order,
+            &ChainConfig::goerli(),
+            &FillAmount::Full,
+            NOW,
+            U256::ZERO,
+            &funds,
+        )
+        .unwrap();
+        assert_eq!(
+            verdict.problems,
+            vec![Unfillable::UnreadableFunds {
+                token: WETH,
+                call: "allowance"
+            }]
+        );
+    }
+
+    #[test]
+    fn other_offer_items_are_unchecked() {
+        let nft = address!("5FbDB2315678afecb367f032d93F642f64180aa3");
+        let order = OrderBuilder::new(OFFERER)
+            .offer_erc721(nft, U256::from(1))
+            .consideration_erc20(USDC, U256::from(1500))
+            .start_time(NOW)
+            .duration(600)
+            .build()
+            .unwrap();
+        let verdict = FillabilityVerdict::assess(
+            &order,
+            &ChainConfig::goerli(),
+            &FillAmount::Full,
+            NOW,
+            U256::ZERO,
+            &HashMap::new(),
+        )
+        .unwrap();
+        assert!(!verdict.is_fillable());
+        assert_eq!(
+            verdict.problems,
+            vec![Unfillable::Unchecked {
+                item_type: ItemType::ERC721,
+                token: nft
+            }]
+        );
+    }
+
+    #[test]
+    fn lists_every_problem() {
+        let chain = ChainConfig::goerli();
+        let mut order = order();
+        order.conduitKey =
+            b256!("0000007b02230091a7ed01230072f7006a004d60a8d4e71d599b8104250f0000");
+        let funds = HashMap::from([(
+            WETH,
+            TokenFunds {
+                balance: Some(U256::from(999)),
+                allowance: Some(U256::ZERO),
+            },
+        )]);
+        let verdict = FillabilityVerdict::assess(
+            &order,
+            &chain,
+            &FillAmount::Full,
+            NOW + 600,
+            U256::from(1),
+            &funds,
+        )
+        .unwrap();
+        assert_eq!(
+            verdict.problems,
+            vec![
+                Unfillable::Expired {
+                    end_time: U256::from(NOW + 600)
+                },
+                Unfillable::StaleCounter {
+                    signed_at: U256::ZERO,
+                    current: U256::from(1)
+                },
+                Unfillable::InsufficientBalance {
+                    token: WETH,
+                    needed: U256::from(1000),
+                    balance: U256::from(999)
+                },
+                Unfillable::InsufficientAllowance {
+                    token: WETH,
+                    spender: address!("1E0049783F008A0085193E00003D00cd54003c71"),
+                    needed: U256::from(1000),
+                    allowance: U256::ZERO
+                },
+            ]
+        );
+
+        let early = FillabilityVerdict::assess(
+            &order,
+            &chain,
+            &FillAmount::Full,
+            NOW - 1,
+            U256::ZERO,
+            &funds,
+        )
+        .unwrap();
+        assert_eq!(
+            early.problems[0],
+            Unfillable::NotStarted {
+                start_time: U256::from(NOW)
+            }
+        );
+    }
+}
@@ -1,8 +1,27 @@
+pub mod auction;
+pub mod chains;
 pub mod constants;
+pub mod conversions;
+pub mod criteria;
 pub mod events;
+pub mod fee;
+pub mod fill;
+pub mod fillability;
+pub mod market;
+pub mod matching;
+pub mod order_builder;
 pub mod responses;
 pub mod seaport;
+pub mod serde_utils;
+pub mod status;
+pub mod tokens;
 
 use ethers::prelude::abigen;
 
 abigen!(IERC20, "./abi/IERC20.json");
+
+// the Seaport reads batched into multicalls; everything else goes through `seaport`
+abigen!(
+    ISeaport,
+    r#"[function getCounter(address offerer) external view returns (uint256 counter)]"#
+);
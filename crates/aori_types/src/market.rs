@@ -0,0 +1,370 @@
+use std::{cmp::Ordering, fmt};
+
+use alloy_primitives::{Address, U256, U512};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::{events::OrderCreatedData, tokens::TokenRegistry};
+
+/// Which side of a pair an order is on, from the point of view of its base token.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Side {
+    /// Buying the base token with the quote token: a bid.
+    #[serde(rename = "BUY")]
+    Buy,
+    /// Selling the base token for the quote token: an ask.
+    #[serde(rename = "SELL")]
+    Sell,
+}
+
+impl Side {
+    pub fn opposite(self) -> Self {
+        match self {
+            Side::Buy => Side::Sell,
+            Side::Sell => Side::Buy,
+        }
+    }
+}
+
+impl fmt::Display for Side {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Side::Buy => "BUY",
+            Side::Sell => "SELL",
+        })
+    }
+}
+
+/// Two tokens traded against each other on one chain, priced in `quote` per `base`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Pair {
+    pub chain_id: u64,
+    pub base: Address,
+    pub quote: Address,
+}
+
+impl Pair {
+    pub fn new(chain_id: u64, base: Address, quote: Address) -> Self {
+        Self {
+            chain_id,
+            base,
+            quote,
+        }
+    }
+
+    /// The pair of `a` and `b` with the lower address as the base, so both directions of a
+    /// trade land on the same pair.
+    pub fn canonical(chain_id: u64, a: Address, b: Address) -> Self {
+        if a <= b {
+            Self::new(chain_id, a, b)
+        } else {
+            Self::new(chain_id, b, a)
+        }
+    }
+
+    pub fn inverse(&self) -> Self {
+        Self::new(self.chain_id, self.quote, self.base)
+    }
+
+    /// The side of an order giving `input_token` for `output_token`, or `None` if it trades
+    /// something else.
+    pub fn side_of(&self, input_token: Address, output_token: Address) -> Option<Side> {
+        if (input_token, output_token) == (self.base, self.quote) {
+            Some(Side::Sell)
+        } else if (input_token, output_token) == (self.quote, self.base) {
+            Some(Side::Buy)
+        } else {
+            None
+        }
+    }
+}
+
+/// An exact price in quote tokens per base token, kept as the raw amounts that make it up so
+/// comparisons don't lose precision.
+#[derive(Clone, Copy, Debug)]
+pub struct Price {
+    base_amount: U256,
+    base_decimals: u8,
+    quote_amount: U256,
+    quote_decimals: u8,
+}
+
+impl Price {
+    /// The price of trading `base_amount` raw units of the base token for `quote_amount` raw
+    /// units of the quote token. Returns `None` for a zero base amount.
+    pub fn new(
+        base_amount: U256,
+        base_decimals: u8,
+        quote_amount: U256,
+        quote_decimals: u8,
+    ) -> Option<Self> {
+        (!base_amount.is_zero()).then_some(Self {
+            base_amount,
+            base_decimals,
+            quote_amount,
+            quote_decimals,
+        })
+    }
+
+    /// Whole quote tokens per whole base token, e.g. 1500 for WETH/USDC.
+    pub fn to_decimal(&self) -> eyre::Result<Decimal> {
+        // keep 18 digits after the point before handing the quotient to `Decimal`, moving
+        // only the difference in decimals onto one side of the fraction
+        let (base_decimals, quote_decimals) =
+            (self.base_decimals as u32, self.quote_decimals as u32);
+        let too_large = || {
+            eyre::eyre!(
+                "price: {}/{} is too large for a decimal.",
+                self.quote_amount,
+                self.base_amount
+            )
+        };
+        let numerator = scaled(
+            self.quote_amount,
+            (base_decimals + 18).saturating_sub(quote_decimals),
+        )
+        .ok_or_else(too_large)?;
+        // a denominator past a uint512 leaves nothing within 18 digits
+        let scaled = scaled(
+            self.base_amount,
+            quote_decimals.saturating_sub(base_decimals + 18),
+        )
+        .map_or(U512::ZERO, |denominator| numerator / denominator);
+        let mantissa = i128::try_from(scaled)
+            .map_err(|_| eyre::eyre!("price: {} is too large for a decimal.", scaled))?;
+        Ok(Decimal::try_from_i128_with_scale(mantissa, 18)
+            .map_err(|_| eyre::eyre!("price: {} does not fit in a decimal.", scaled))?
+            .normalize())
+    }
+
+    /// The raw quote amount `base_amount` raw base units are worth at this price, rounded
+    /// down.
+    pub fn quote_for(&self, base_amount: U256) -> U256 {
+        let quote =
+            U512::from(base_amount) * U512::from(self.quote_amount) / U512::from(self.base_amount);
+        if quote > U512::from(U256::MAX) {
+            U256::MAX
+        } else {
+            U256::from_limbs_slice(&quote.as_limbs()[..4])
+        }
+    }
+}
+
+// `amount * 10^exponent`, or `None` if it doesn't fit in a uint512
+fn scaled(amount: U256, exponent: u32) -> Option<U512> {
+    U512::from(10)
+        .checked_pow(U512::from(exponent))?
+        .checked_mul(U512::from(amount))
+}
+
+impl Ord for Price {
+    // quote_a * 10^base_decimals_a / (base_a * 10^quote_decimals_a) against the same for b,
+    // cross-multiplied; the amounts multiply within a uint512, and the powers of ten are
+    // netted onto one side, where overflowing means that side is the larger
+    fn cmp(&self, other: &Self) -> Ordering {
+        let a = U512::from(self.quote_amount) * U512::from(other.base_amount);
+        let b = U512::from(other.quote_amount) * U512::from(self.base_amount);
+        let a_exponent = self.base_decimals as u32 + other.quote_decimals as u32;
+        let b_exponent = other.base_decimals as u32 + self.quote_decimals as u32;
+        if a_exponent >= b_exponent {
+            cmp_scaled(a, a_exponent - b_exponent, b)
+        } else {
+            cmp_scaled(b, b_exponent - a_exponent, a).reverse()
+        }
+    }
+}
+
+// `a * 10^exponent` against `b`
+fn cmp_scaled(a: U512, exponent: u32, b: U512) -> Ordering {
+    if a.is_zero() {
+        return a.cmp(&b);
+    }
+    match U512::from(10)
+        .checked_pow(U512::from(exponent))
+        .and_then(|power| power.checked_mul(a))
+    {
+        Some(a) => a.cmp(&b),
+        None => Ordering::Greater,
+    }
+}
+
+impl PartialOrd for Price {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Price {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Price {}
+
+impl OrderCreatedData {
+    /// The canonical pair the order trades on.
+    pub fn pair(&self) -> Pair {
+        Pair::canonical(self.chain_id as u64, self.input_token, self.output_token)
+    }
+
+    /// Whether the order is a bid or an ask on its canonical pair, or `None` if it gives and
+    /// wants the same token, which makes it neither.
+    pub fn side(&self) -> Option<Side> {
+        if self.input_token == self.output_token {
+            return None;
+        }
+        self.pair().side_of(self.input_token, self.output_token)
+    }
+
+    /// The order's limit price on `pair`, using the decimals in `tokens`.
+    pub fn price_on(&self, pair: &Pair, tokens: &TokenRegistry) -> eyre::Result<Price> {
+        let side = pair
+            .side_of(self.input_token, self.output_token)
+            .ok_or(eyre::eyre!(
+                "price: order {} does not trade on this pair.",
+                self.order_hash
+            ))?;
+        let (base_amount, quote_amount) = match side {
+            Side::Sell => (self.input_amount, self.output_amount),
+            Side::Buy => (self.output_amount, self.input_amount),
+        };
+        let decimals = |token| {
+            tokens
+                .get(pair.chain_id, token)
+                .map(|token| token.decimals)
+                .ok_or(eyre::eyre!(
+                    "price: {} is not registered on chain {}.",
+                    token,
+                    pair.chain_id
+                ))
+        };
+        Price::new(
+            base_amount,
+            decimals(pair.base)?,
+            quote_amount,
+            decimals(pair.quote)?,
+        )
+        .ok_or(eyre::eyre!(
+            "price: order {} has no base amount.",
+            self.order_hash
+        ))
+    }
+
+    /// The order's limit price on its canonical pair.
+    pub fn price(&self, tokens: &TokenRegistry) -> eyre::Result<Price> {
+        self.price_on(&self.pair(), tokens)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::{OrderCreationData, OrderParameters};
+    use alloy_primitives::{address, B256};
+    use rust_decimal_macros::dec;
+
+    const OFFERER: Address = address!("f39Fd6e51aad88F6F4ce6aB8827279cffFb92266");
+    const WETH: Address = address!("2715Ccea428F8c7694f7e78B2C89cb454c5F7294");
+    const USDC: Address = address!("D3664B5e72B46eaba722aB6f43c22dBF40181954");
+
+    fn order(
+        input_token: Address,
+        input_amount: u128,
+        output_token: Address,
+        output_amount: u128,
+    ) -> OrderCreatedData {
+        let (input_amount, output_amount) = (U256::from(input_amount), U256::from(output_amount));
+        OrderCreatedData {
+            order: OrderCreationData {
+                parameters: OrderParameters::limit_order(
+                    OFFERER,
+                    input_token,
+                    input_amount,
+                    output_token,
+                    output_amount,
+                ),
+                signature: "0x".to_string(),
+            },
+            order_hash: B256::ZERO,
+            input_token,
+            output_token,
+            input_amount,
+            output_amount,
+            chain_id: 5,
+            active: true,
+            created_at: 0,
+            last_updated_at: 0,
+            is_public: true,
+            rate: None,
+        }
+    }
+
+    #[test]
+    fn canonical_pairs() {
+        let pair = Pair::canonical(5, WETH, USDC);
+        assert_eq!(pair, Pair::canonical(5, USDC, WETH));
+        assert_eq!(pair.base, WETH);
+        assert_eq!(pair.side_of(WETH, USDC), Some(Side::Sell));
+        assert_eq!(pair.side_of(USDC, WETH), Some(Side::Buy));
+        assert_eq!(pair.side_of(USDC, OFFERER), None);
+        assert_eq!(pair.inverse().side_of(WETH, USDC), Some(Side::Buy));
+    }
+
+    #[test]
+    fn orders_are_bids_or_asks() {
+        let ask = order(WETH, 1000000000000000000, USDC, 1500000000);
+        let bid = order(USDC, 1400000000, WETH, 1000000000000000000);
+        assert_eq!(ask.pair(), bid.pair());
+        assert_eq!(ask.side(), Some(Side::Sell));
+        assert_eq!(bid.side(), Some(Side::Buy));
+        assert_eq!(bid.side().map(Side::opposite), ask.side());
+        assert_eq!(order(WETH, 1, WETH, 1).side(), None);
+    }
+
+    #[test]
+    fn prices_use_token_decimals() {
+        let tokens = TokenRegistry::goerli();
+        let ask = order(WETH, 1000000000000000000, USDC, 1500000000);
+        let bid = order(USDC, 700000000, WETH, 500000000000000000);
+
+        let ask_price = ask.price(&tokens).unwrap();
+        let bid_price = bid.price(&tokens).unwrap();
+        assert_eq!(ask_price.to_decimal().unwrap(), dec!(1500));
+        assert_eq!(bid_price.to_decimal().unwrap(), dec!(1400));
+        // the spread is positive, so these two don't cross
+        assert!(bid_price < ask_price);
+        assert_eq!(
+            ask_price.quote_for(U256::from(250000000000000000_u64)),
+            U256::from(375000000)
+        );
+
+        // the same price, expressed with different amounts
+        let same = order(WETH, 2000000000000000000, USDC, 3000000000);
+        assert_eq!(same.price(&tokens).unwrap(), ask_price);
+
+        let unknown = order(WETH, 1, OFFERER, 1);
+        assert!(unknown.price(&tokens).is_err());
+    }
+
+    #[test]
+    fn prices_at_the_extremes() {
+        let one = Price::new(U256::from(1), 0, U256::from(1), 0).unwrap();
+        let also_one = Price::new(U256::MAX, 255, U256::MAX, 255).unwrap();
+        assert_eq!(one, also_one);
+        assert_eq!(also_one.to_decimal().unwrap(), dec!(1));
+
+        let tiny = Price::new(U256::MAX, 0, U256::from(1), 255).unwrap();
+        let huge = Price::new(U256::from(1), 255, U256::MAX, 0).unwrap();
+        assert!(tiny < one && one < huge);
+        assert_eq!(tiny.to_decimal().unwrap(), dec!(0));
+        assert!(huge.to_decimal().is_err());
+        assert_eq!(huge.quote_for(U256::MAX), U256::MAX);
+    }
+
+    #[test]
+    fn side_serializes_like_the_api() {
+        assert_eq!(serde_json::to_value(Side::Buy).unwrap(), "BUY");
+        assert_eq!(Side::Sell.to_string(), "SELL");
+    }
+}
@@ -0,0 +1,245 @@
+use alloy_primitives::{Address, B256, U256};
+
+use crate::seaport::{
+    encode_match_orders, Fulfillment, FulfillmentComponent, ItemType, Order, OrderComponents,
+    SpentItem,
+};
+
+/// Orders that settle against each other in a single `matchOrders` call: either every
+/// order fills or the whole transaction reverts, so no leg is left hanging.
+#[derive(Clone, Debug)]
+pub struct OrderMatch {
+    pub orders: Vec<Order>,
+    pub fulfillments: Vec<Fulfillment>,
+    /// What is left of the offers once every consideration item is paid. Seaport sends it
+    /// to whoever submits the match.
+    pub surplus: Vec<SpentItem>,
+}
+
+// offer items Seaport lets one fulfillment draw on together: the same asset, from the same
+// offerer through the same conduit
+struct OfferGroup {
+    item_type: u8,
+    token: Address,
+    identifier: U256,
+    offerer: Address,
+    conduit_key: B256,
+    components: Vec<FulfillmentComponent>,
+    remaining: U256,
+}
+
+impl OrderMatch {
+    /// Works out how the offers of `orders` pay for their considerations, with item amounts
+    /// as they stand at `timestamp`. Each order comes with its signature and is filled in
+    /// full.
+    ///
+    /// Seaport prices auction items at the block the match lands in, not at `timestamp`, so
+    /// `timestamp` should be when the transaction is expected to be mined. Between the two,
+    /// a moving price can shrink the surplus or leave a consideration unpaid and revert.
+    ///
+    /// Fails if some consideration can't be paid out of the other offers, or if an order
+    /// uses criteria items, which need resolving first.
+    pub fn new(orders: Vec<(OrderComponents, Vec<u8>)>, timestamp: u64) -> eyre::Result<Self> {
+        let mut groups: Vec<OfferGroup> = Vec::new();
+        for (order_index, (order, _)) in orders.iter().enumerate() {
+            let amounts = order.current_offer_amounts(timestamp)?;
+            for (item_index, (item, amount)) in order.offer.iter().zip(amounts).enumerate() {
+                check_item_type(item.itemType)?;
+                if amount.is_zero() {
+                    continue;
+                }
+                let component = FulfillmentComponent {
+                    orderIndex: U256::from(order_index),
+                    itemIndex: U256::from(item_index),
+                };
+                let group = groups.iter_mut().find(|group| {
+                    (group.item_type, group.token, group.identifier)
+                        == (item.itemType, item.token, item.identifierOrCriteria)
+                        && (group.offerer, group.conduit_key) == (order.offerer, order.conduitKey)
+                });
+                match group {
+                    Some(group) => {
+                        group.components.push(component);
+                        group.remaining += amount;
+                    }
+                    None => groups.push(OfferGroup {
+                        item_type: item.itemType,
+                        token: item.token,
+                        identifier: item.identifierOrCriteria,
+                        offerer: order.offerer,
+                        conduit_key: order.conduitKey,
+                        components: vec![component],
+                        remaining: amount,
+                    }),
+                }
+            }
+        }
+
+        let mut fulfillments = Vec::new();
+        for (order_index, (order, _)) in orders.iter().enumerate() {
+            let amounts = order.current_consideration_amounts(timestamp)?;
+            for (item_index, (item, amount)) in order.consideration.iter().zip(amounts).enumerate()
+            {
+                check_item_type(item.itemType)?;
+                let key = (item.itemType, item.token, item.identifierOrCriteria);
+                let mut owed = amount;
+                // Seaport puts whatever a fulfillment doesn't use back on its first component,
+                // so later fulfillments can keep drawing on the same group
+                for group in groups.iter_mut() {
+                    if owed.is_zero() {
+                        break;
+                    }
+                    if (group.item_type, group.token, group.identifier) != key
+                        || group.remaining.is_zero()
+                    {
+                        continue;
+                    }
+                    fulfillments.push(Fulfillment {
+                        offerComponents: group.components.clone(),
+                        considerationComponents: vec![FulfillmentComponent {
+                            orderIndex: U256::from(order_index),
+                            itemIndex: U256::from(item_index),
+                        }],
+                    });
+                    let paid = owed.min(group.remaining);
+                    owed -= paid;
+                    group.remaining -= paid;
+                }
+                if !owed.is_zero() {
+                    return Err(eyre::eyre!(
+                        "match: order {} is short {} of {} item {} in the other offers.",
+                        order_index,
+                        owed,
+                        item.token,
+                        item.identifierOrCriteria
+                    ));
+                }
+            }
+        }
+
+        let mut surplus: Vec<SpentItem> = Vec::new();
+        for group in groups.iter().filter(|group| !group.remaining.is_zero()) {
+            match surplus.iter_mut().find(|item| {
+                (item.itemType as u8, item.token, item.identifier)
+                    == (group.item_type, group.token, group.identifier)
+            }) {
+                Some(item) => item.amount += group.remaining,
+                None => surplus.push(SpentItem {
+                    itemType: ItemType::try_from(group.item_type)
+                        .map_err(|e| eyre::eyre!("itemType: {}", e))?,
+                    token: group.token,
+                    identifier: group.identifier,
+                    amount: group.remaining,
+                }),
+            }
+        }
+
+        let orders = orders
+            .into_iter()
+            .map(|(order, signature)| order.to_order(signature))
+            .collect::<eyre::Result<_>>()?;
+        Ok(Self {
+            orders,
+            fulfillments,
+            surplus,
+        })
+    }
+
+    /// How much of `token` the match leaves over for its submitter.
+    pub fn surplus_of(&self, token: Address) -> U256 {
+        self.surplus
+            .iter()
+            .filter(|item| item.token == token)
+            .fold(U256::ZERO, |total, item| total + item.amount)
+    }
+
+    /// Calldata for the `matchOrders` call that settles the match.
+    pub fn calldata(&self) -> Vec<u8> {
+        encode_match_orders(self.orders.clone(), self.fulfillments.clone())
+    }
+}
+
+fn check_item_type(item_type: u8) -> eyre::Result<()> {
+    match ItemType::try_from(item_type) {
+        Ok(ItemType::ERC721_WITH_CRITERIA | ItemType::ERC1155_WITH_CRITERIA) => Err(eyre::eyre!(
+            "match: criteria items need resolving before they can be matched."
+        )),
+        Ok(_) => Ok(()),
+        Err(_) => Err(eyre::eyre!("match: unknown item type {}.", item_type)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{order_builder::OrderBuilder, seaport::SeaportCall};
+    use alloy_primitives::address;
+
+    const ALICE: Address = address!("f39Fd6e51aad88F6F4ce6aB8827279cffFb92266");
+    const BOB: Address = address!("70997970C51812dc3A010C7d01b50e0d17dc79C8");
+    const WETH: Address = address!("2715Ccea428F8c7694f7e78B2C89cb454c5F7294");
+    const USDC: Address = address!("D3664B5e72B46eaba722aB6f43c22dBF40181954");
+    const NOW: u64 = 1697240202;
+
+    fn order(
+        offerer: Address,
+        offer: (Address, u64),
+        consideration: (Address, u64),
+    ) -> OrderComponents {
+        OrderBuilder::new(offerer)
+            .offer_erc20(offer.0, U256::from(offer.1))
+            .consideration_erc20(consideration.0, U256::from(consideration.1))
+            .start_time(NOW)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn matches_two_crossing_orders() {
+        // alice sells 1 WETH for 1500 USDC; bob pays 1510 USDC for 1 WETH
+        let ask = order(ALICE, (WETH, 1000000), (USDC, 1500000000));
+        let bid = order(BOB, (USDC, 1510000000), (WETH, 1000000));
+        let matched = OrderMatch::new(vec![(ask, vec![1; 65]), (bid, vec![2; 65])], NOW).unwrap();
+
+        assert_eq!(matched.fulfillments.len(), 2);
+        let alice_paid = &matched.fulfillments[0];
+        assert_eq!(alice_paid.offerComponents[0].orderIndex, U256::from(1));
+        assert_eq!(alice_paid.considerationComponents[0].orderIndex, U256::ZERO);
+        assert_eq!(matched.surplus_of(USDC), U256::from(10000000));
+        assert_eq!(matched.surplus_of(WETH), U256::ZERO);
+
+        match SeaportCall::decode(&matched.calldata()).unwrap() {
+            SeaportCall::MatchOrders(call) => {
+                assert_eq!(call.orders.len(), 2);
+                assert_eq!(call.orders[1].signature, vec![2; 65]);
+                assert_eq!(call.fulfillments.len(), 2);
+            }
+            call => panic!("decoded {:?}", call),
+        }
+    }
+
+    #[test]
+    fn splits_a_consideration_across_offerers() {
+        let ask = order(ALICE, (WETH, 2000000), (USDC, 3000000000));
+        let bob = order(BOB, (USDC, 2000000000), (WETH, 1000000));
+        let carol = order(
+            address!("3C44CdDdB6a900fa2b585dd299e03d12FA4293BC"),
+            (USDC, 1000000000),
+            (WETH, 1000000),
+        );
+        let matched =
+            OrderMatch::new(vec![(ask, vec![]), (bob, vec![]), (carol, vec![])], NOW).unwrap();
+
+        // alice's USDC comes from bob and carol; each of their WETH comes from alice
+        assert_eq!(matched.fulfillments.len(), 4);
+        assert!(matched.surplus.is_empty());
+    }
+
+    #[test]
+    fn rejects_uncovered_considerations() {
+        let ask = order(ALICE, (WETH, 1000000), (USDC, 1500000000));
+        let bid = order(BOB, (USDC, 1400000000), (WETH, 1000000));
+        assert!(OrderMatch::new(vec![(ask.clone(), vec![]), (bid, vec![])], NOW).is_err());
+        assert!(OrderMatch::new(vec![(ask, vec![])], NOW).is_err());
+    }
+}
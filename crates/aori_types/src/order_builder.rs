@@ -0,0 +1,669 @@
+use alloy_primitives::{Address, B256, U256};
+use chrono::Utc;
+
+use crate::{
+    chains::ChainConfig,
+    constants::{DEFAULT_DURATION, DEFAULT_ZONE_HASH},
+    fee::{split_consideration, Fee},
+    seaport::{ConsiderationItem, ItemType, OfferItem, OrderComponents, OrderType},
+};
+
+/// Builds and validates Seaport `OrderComponents`.
+///
+/// Times are unix timestamps in seconds, as Seaport expects. Anything left unset falls back
+/// to the defaults: the chain's Aori zone and default conduit, a start time of now and
+/// `DEFAULT_DURATION`. The salt is random unless set.
+///
+/// ```ignore
+/// let order = OrderBuilder::new(wallet)
+///     .offer_erc20(weth, U256::from(1000000000000000000_u128))
+///     .consideration_erc20(usdc, U256::from(1500000000_u64))
+///     .duration(60 * 60)
+///     .build()?;
+/// ```
+#[derive(Clone, Debug)]
+pub struct OrderBuilder {
+    offerer: Address,
+    zone: Address,
+    offer: Vec<OfferItem>,
+    consideration: Vec<ConsiderationItem>,
+    fees: Vec<Fee>,
+    order_type: OrderType,
+    start_time: Option<u64>,
+    end_time: Option<u64>,
+    duration: u64,
+    zone_hash: B256,
+    salt: Option<U256>,
+    conduit_key: B256,
+    counter: U256,
+}
+
+impl OrderBuilder {
+    /// A builder for an order on the default chain.
+    pub fn new(offerer: Address) -> Self {
+        Self::for_chain(offerer, &ChainConfig::default())
+    }
+
+    pub fn for_chain(offerer: Address, chain: &ChainConfig) -> Self {
+        Self {
+            offerer,
+            zone: chain.zone,
+            offer: Vec::new(),
+            consideration: Vec::new(),
+            fees: Vec::new(),
+            order_type: OrderType::PARTIAL_RESTRICTED,
+            start_time: None,
+            end_time: None,
+            duration: DEFAULT_DURATION,
+            zone_hash: DEFAULT_ZONE_HASH.into(),
+            salt: None,
+            conduit_key: chain.default_conduit_key(),
+            counter: U256::ZERO,
+        }
+    }
+
+    pub fn offer_erc20(self, token: Address, amount: U256) -> Self {
+        self.offer_erc20_auction(token, amount, amount)
+    }
+
+    /// Offers an amount of `token` that moves linearly from `start_amount` to `end_amount`
+    /// over the order's lifetime. A falling offer makes the order an ascending auction.
+    pub fn offer_erc20_auction(self, token: Address, start_amount: U256, end_amount: U256) -> Self {
+        self.offer(ItemType::ERC20, token, U256::ZERO, start_amount, end_amount)
+    }
+
+    /// Offers ETH. Seaport can only move an offerer's ETH when the order is matched (see
+    /// `matchOrders`), as nothing is escrowed for it.
+    pub fn offer_native(self, amount: U256) -> Self {
+        self.offer(ItemType::NATIVE, Address::ZERO, U256::ZERO, amount, amount)
+    }
+
+    pub fn offer_erc721(self, token: Address, token_id: U256) -> Self {
+        self.offer(
+            ItemType::ERC721,
+            token,
+            token_id,
+            U256::from(1),
+            U256::from(1),
+        )
+    }
+
+    pub fn offer_erc1155(self, token: Address, token_id: U256, amount: U256) -> Self {
+        self.offer(ItemType::ERC1155, token, token_id, amount, amount)
+    }
+
+    /// Offers any one token of `token` whose id is in the set committed to by `criteria_root`
+    /// (see `criteria::criteria_root`); a zero root accepts any id.
+    pub fn offer_erc721_criteria(self, token: Address, criteria_root: U256) -> Self {
+        self.offer(
+            ItemType::ERC721_WITH_CRITERIA,
+            token,
+            criteria_root,
+            U256::from(1),
+            U256::from(1),
+        )
+    }
+
+    pub fn offer_erc1155_criteria(self, token: Address, criteria_root: U256, amount: U256) -> Self {
+        self.offer(
+            ItemType::ERC1155_WITH_CRITERIA,
+            token,
+            criteria_root,
+            amount,
+            amount,
+        )
+    }
+
+    /// Asks for `amount` of `token`, paid to the offerer.
+    pub fn consideration_erc20(self, token: Address, amount: U256) -> Self {
+        let recipient = self.offerer;
+        self.consideration_erc20_to(token, amount, recipient)
+    }
+
+    pub fn consideration_erc20_to(self, token: Address, amount: U256, recipient: Address) -> Self {
+        self.consideration_erc20_auction_to(token, amount, amount, recipient)
+    }
+
+    /// Asks for an amount of `token` that moves linearly from `start_amount` to `end_amount`
+    /// over the order's lifetime, paid to the offerer. A falling consideration makes the order
+    /// a descending (dutch) auction.
+    pub fn consideration_erc20_auction(
+        self,
+        token: Address,
+        start_amount: U256,
+        end_amount: U256,
+    ) -> Self {
+        let recipient = self.offerer;
+        self.consideration_erc20_auction_to(token, start_amount, end_amount, recipient)
+    }
+
+    pub fn consideration_erc20_auction_to(
+        self,
+        token: Address,
+        start_amount: U256,
+        end_amount: U256,
+        recipient: Address,
+    ) -> Self {
+        self.consideration(
+            ItemType::ERC20,
+            token,
+            U256::ZERO,
+            start_amount,
+            end_amount,
+            recipient,
+        )
+    }
+
+    /// Asks for `amount` of ETH, as opposed to WETH, paid to the offerer.
+    pub fn consideration_native(self, amount: U256) -> Self {
+        let recipient = self.offerer;
+        self.consideration_native_to(amount, recipient)
+    }
+
+    pub fn consideration_native_to(self, amount: U256, recipient: Address) -> Self {
+        self.consideration(
+            ItemType::NATIVE,
+            Address::ZERO,
+            U256::ZERO,
+            amount,
+            amount,
+            recipient,
+        )
+    }
+
+    pub fn consideration_erc721(self, token: Address, token_id: U256) -> Self {
+        let recipient = self.offerer;
+        self.consideration(
+            ItemType::ERC721,
+            token,
+            token_id,
+            U256::from(1),
+            U256::from(1),
+            recipient,
+        )
+    }
+
+    pub fn consideration_erc1155(self, token: Address, token_id: U256, amount: U256) -> Self {
+        let recipient = self.offerer;
+        self.consideration(
+            ItemType::ERC1155,
+            token,
+            token_id,
+            amount,
+            amount,
+            recipient,
+        )
+    }
+
+    /// Asks for any one token of `token` whose id is in the set committed to by
+    /// `criteria_root`, e.g. a collection or trait bid; a zero root accepts any id.
+    pub fn consideration_erc721_criteria(self, token: Address, criteria_root: U256) -> Self {
+        let recipient = self.offerer;
+        self.consideration(
+            ItemType::ERC721_WITH_CRITERIA,
+            token,
+            criteria_root,
+            U256::from(1),
+            U256::from(1),
+            recipient,
+        )
+    }
+
+    pub fn consideration_erc1155_criteria(
+        self,
+        token: Address,
+        criteria_root: U256,
+        amount: U256,
+    ) -> Self {
+        let recipient = self.offerer;
+        self.consideration(
+            ItemType::ERC1155_WITH_CRITERIA,
+            token,
+            criteria_root,
+            amount,
+            amount,
+            recipient,
+        )
+    }
+
+    fn offer(
+        self,
+        item_type: ItemType,
+        token: Address,
+        identifier_or_criteria: U256,
+        start_amount: U256,
+        end_amount: U256,
+    ) -> Self {
+        self.offer_item(OfferItem {
+            itemType: item_type as u8,
+            token,
+            identifierOrCriteria: identifier_or_criteria,
+            startAmount: start_amount,
+            endAmount: end_amount,
+        })
+    }
+
+    fn consideration(
+        self,
+        item_type: ItemType,
+        token: Address,
+        identifier_or_criteria: U256,
+        start_amount: U256,
+        end_amount: U256,
+        recipient: Address,
+    ) -> Self {
+        self.consideration_item(ConsiderationItem {
+            itemType: item_type as u8,
+            token,
+            identifierOrCriteria: identifier_or_criteria,
+            startAmount: start_amount,
+            endAmount: end_amount,
+            recipient,
+        })
+    }
+
+    pub fn offer_item(mut self, item: OfferItem) -> Self {
+        self.offer.push(item);
+        self
+    }
+
+    pub fn consideration_item(mut self, item: ConsiderationItem) -> Self {
+        self.consideration.push(item);
+        self
+    }
+
+    /// Pays `bps` basis points of the offerer's proceeds to `recipient`. Fees are carved out of
+    /// the first consideration item paid to the offerer when the order is built, so the taker
+    /// pays the same total and the offerer receives that much less.
+    pub fn fee(mut self, recipient: Address, bps: u16) -> Self {
+        self.fees.push(Fee::new(recipient, bps));
+        self
+    }
+
+    pub fn order_type(mut self, order_type: OrderType) -> Self {
+        self.order_type = order_type;
+        self
+    }
+
+    pub fn zone(mut self, zone: Address) -> Self {
+        self.zone = zone;
+        self
+    }
+
+    pub fn zone_hash(mut self, zone_hash: B256) -> Self {
+        self.zone_hash = zone_hash;
+        self
+    }
+
+    pub fn conduit_key(mut self, conduit_key: B256) -> Self {
+        self.conduit_key = conduit_key;
+        self
+    }
+
+    pub fn start_time(mut self, seconds: u64) -> Self {
+        self.start_time = Some(seconds);
+        self
+    }
+
+    /// Sets an absolute end time, overriding `duration`.
+    pub fn end_time(mut self, seconds: u64) -> Self {
+        self.end_time = Some(seconds);
+        self
+    }
+
+    pub fn duration(mut self, seconds: u64) -> Self {
+        self.duration = seconds;
+        self
+    }
+
+    pub fn salt(mut self, salt: U256) -> Self {
+        self.salt = Some(salt);
+        self
+    }
+
+    /// The offerer's current Seaport counter; orders signed at a stale counter can't be filled.
+    pub fn counter(mut self, counter: U256) -> Self {
+        self.counter = counter;
+        self
+    }
+
+    pub fn build(mut self) -> eyre::Result<OrderComponents> {
+        if !self.fees.is_empty() {
+            let index = self
+                .consideration
+                .iter()
+                .position(|item| item.recipient == self.offerer)
+                .ok_or(eyre::eyre!(
+                    "fee: no consideration item is paid to the offerer."
+                ))?;
+            let items = split_consideration(&self.consideration[index], &self.fees)?;
+            self.consideration.splice(index..=index, items);
+        }
+
+        let start_time = self
+            .start_time
+            .unwrap_or_else(|| Utc::now().timestamp() as u64);
+        let end_time = self
+            .end_time
+            .unwrap_or_else(|| start_time.saturating_add(self.duration));
+        let salt = self
+            .salt
+            .unwrap_or_else(|| U256::from_be_bytes(ethers::core::rand::random::<[u8; 32]>()));
+
+        let order = OrderComponents {
+            offerer: self.offerer,
+            zone: self.zone,
+            offer: self.offer,
+            consideration: self.consideration,
+            orderType: self.order_type as u8,
+            startTime: U256::from(start_time),
+            endTime: U256::from(end_time),
+            zoneHash: self.zone_hash,
+            salt,
+            conduitKey: self.conduit_key,
+            counter: self.counter,
+        };
+        validate(&order)?;
+        Ok(order)
+    }
+}
+
+fn validate(order: &OrderComponents) -> eyre::Result<()> {
+    if order.offerer == Address::ZERO {
+        return Err(eyre::eyre!("offerer: must not be the zero address."));
+    }
+    if order.offer.is_empty() {
+        return Err(eyre::eyre!("offer: order needs at least one offer item."));
+    }
+    if order.consideration.is_empty() {
+        return Err(eyre::eyre!(
+            "consideration: order needs at least one consideration item."
+        ));
+    }
+    if order.endTime <= order.startTime {
+        return Err(eyre::eyre!(
+            "endTime: {} is not after startTime {}.",
+            order.endTime,
+            order.startTime
+        ));
+    }
+    // a millisecond timestamp would put the order thousands of years in the future
+    if order.startTime > U256::from(u32::MAX) {
+        return Err(eyre::eyre!(
+            "startTime: {} looks like milliseconds; Seaport uses seconds.",
+            order.startTime
+        ));
+    }
+
+    let items = order
+        .offer
+        .iter()
+        .map(|item| {
+            (
+                "offer",
+                item.itemType,
+                item.token,
+                item.identifierOrCriteria,
+                item.startAmount,
+                item.endAmount,
+            )
+        })
+        .chain(order.consideration.iter().map(|item| {
+            (
+                "consideration",
+                item.itemType,
+                item.token,
+                item.identifierOrCriteria,
+                item.startAmount,
+                item.endAmount,
+            )
+        }));
+    for (side, item_type, token, identifier, start_amount, end_amount) in items {
+        if start_amount.is_zero() || end_amount.is_zero() {
+            return Err(eyre::eyre!("{}: item amounts must be non-zero.", side));
+        }
+        match ItemType::try_from(item_type) {
+            Ok(ItemType::NATIVE) if token != Address::ZERO || !identifier.is_zero() => {
+                return Err(eyre::eyre!(
+                    "{}: native items must have a zero token and identifier.",
+                    side
+                ));
+            }
+            Ok(ItemType::ERC20) if !identifier.is_zero() => {
+                return Err(eyre::eyre!(
+                    "{}: erc20 items must have a zero identifier.",
+                    side
+                ));
+            }
+            Ok(ItemType::ERC721 | ItemType::ERC721_WITH_CRITERIA)
+                if start_amount != U256::from(1) || end_amount != U256::from(1) =>
+            {
+                return Err(eyre::eyre!(
+                    "{}: erc721 items must have an amount of 1.",
+                    side
+                ));
+            }
+            Ok(_) => {}
+            Err(_) => return Err(eyre::eyre!("{}: unknown item type {}.", side, item_type)),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+
+    const OFFERER: Address = address!("f39Fd6e51aad88F6F4ce6aB8827279cffFb92266");
+    const WETH: Address = address!("2715Ccea428F8c7694f7e78B2C89cb454c5F7294");
+    const USDC: Address = address!("D3664B5e72B46eaba722aB6f43c22dBF40181954");
+
+    fn limit_order() -> OrderBuilder {
+        OrderBuilder::new(OFFERER)
+            .offer_erc20(WETH, U256::from(1000000000000000000_u128))
+            .consideration_erc20(USDC, U256::from(1500000000_u64))
+    }
+
+    #[test]
+    fn builds_with_defaults() {
+        let order = limit_order().build().unwrap();
+        assert_eq!(order.zone, ChainConfig::goerli().zone);
+        assert_eq!(order.conduitKey, B256::ZERO);
+        assert_eq!(order.orderType, OrderType::PARTIAL_RESTRICTED as u8);
+        assert_eq!(order.consideration[0].recipient, OFFERER);
+        assert_eq!(
+            order.endTime - order.startTime,
+            U256::from(DEFAULT_DURATION)
+        );
+        // seconds, not milliseconds
+        assert!(order.startTime <= U256::from(Utc::now().timestamp()));
+        assert_ne!(
+            limit_order().build().unwrap().salt,
+            limit_order().build().unwrap().salt
+        );
+    }
+
+    #[test]
+    fn uses_the_chain_zone_and_conduit() {
+        let chain = ChainConfig {
+            zone: OFFERER,
+            conduit_keys: vec![B256::repeat_byte(1)],
+            ..ChainConfig::goerli()
+        };
+        let order = OrderBuilder::for_chain(OFFERER, &chain)
+            .offer_erc20(WETH, U256::from(1000000000000000000_u128))
+            .consideration_erc20(USDC, U256::from(1500000000_u64))
+            .build()
+            .unwrap();
+        assert_eq!(order.zone, OFFERER);
+        assert_eq!(order.conduitKey, B256::repeat_byte(1));
+    }
+
+    #[test]
+    fn explicit_times_salt_and_counter() {
+        let order = limit_order()
+            .start_time(1697240202)
+            .duration(60)
+            .salt(U256::from(42))
+            .counter(U256::from(3))
+            .build()
+            .unwrap();
+        assert_eq!(order.startTime, U256::from(1697240202));
+        assert_eq!(order.endTime, U256::from(1697240262));
+        assert_eq!(order.salt, U256::from(42));
+        assert_eq!(order.counter, U256::from(3));
+
+        let order = limit_order()
+            .start_time(1697240202)
+            .end_time(1697250000)
+            .build()
+            .unwrap();
+        assert_eq!(order.endTime, U256::from(1697250000));
+    }
+
+    #[test]
+    fn rejects_invalid_orders() {
+        assert!(OrderBuilder::new(OFFERER)
+            .offer_erc20(WETH, U256::from(1))
+            .build()
+            .is_err());
+        assert!(OrderBuilder::new(Address::ZERO)
+            .offer_erc20(WETH, U256::from(1))
+            .consideration_erc20(USDC, U256::from(1))
+            .build()
+            .is_err());
+        assert!(limit_order().offer_erc20(WETH, U256::ZERO).build().is_err());
+        assert!(limit_order()
+            .start_time(1697240202)
+            .end_time(1697240202)
+            .build()
+            .is_err());
+        assert!(limit_order()
+            .start_time(Utc::now().timestamp_millis() as u64)
+            .build()
+            .is_err());
+        assert!(limit_order()
+            .offer_item(OfferItem {
+                itemType: ItemType::ERC721 as u8,
+                token: WETH,
+                identifierOrCriteria: U256::from(1),
+                startAmount: U256::from(2),
+                endAmount: U256::from(2),
+            })
+            .build()
+            .is_err());
+        assert!(limit_order()
+            .offer_item(OfferItem {
+                itemType: 9,
+                token: WETH,
+                identifierOrCriteria: U256::ZERO,
+                startAmount: U256::from(1),
+                endAmount: U256::from(1),
+            })
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn fees_come_out_of_the_offerers_proceeds() {
+        let protocol = address!("70997970C51812dc3A010C7d01b50e0d17dc79C8");
+        let order = limit_order().fee(protocol, 30).build().unwrap();
+        assert_eq!(order.consideration.len(), 2);
+        assert_eq!(order.consideration[0].recipient, OFFERER);
+        assert_eq!(order.consideration[0].startAmount, U256::from(1495500000));
+        assert_eq!(order.consideration[1].recipient, protocol);
+        assert_eq!(order.consideration[1].startAmount, U256::from(4500000));
+        assert_eq!(
+            order
+                .to_order_parameters()
+                .unwrap()
+                .totalOriginalConsiderationItems,
+            U256::from(2)
+        );
+
+        // a payment to someone else is left alone
+        let order = OrderBuilder::new(OFFERER)
+            .offer_erc20(WETH, U256::from(1000000000000000000_u128))
+            .consideration_erc20_to(USDC, U256::from(1000000), protocol)
+            .consideration_erc20(USDC, U256::from(1500000000_u64))
+            .fee(protocol, 30)
+            .build()
+            .unwrap();
+        assert_eq!(order.consideration.len(), 3);
+        assert_eq!(order.consideration[0].startAmount, U256::from(1000000));
+        assert_eq!(order.consideration[1].startAmount, U256::from(1495500000));
+
+        assert!(OrderBuilder::new(OFFERER)
+            .offer_erc20(WETH, U256::from(1))
+            .consideration_erc20_to(USDC, U256::from(1000000), protocol)
+            .fee(protocol, 30)
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn native_legs() {
+        let order = OrderBuilder::new(OFFERER)
+            .offer_erc20(USDC, U256::from(1500000000_u64))
+            .consideration_native(U256::from(1000000000000000000_u128))
+            .build()
+            .unwrap();
+        let item = &order.consideration[0];
+        assert!(item.is_native());
+        assert_eq!(item.token, Address::ZERO);
+        assert_eq!(item.recipient, OFFERER);
+        assert!(!order.offer[0].is_native());
+
+        // a native item pointing at WETH is a mix-up, not an ETH leg
+        assert!(OrderBuilder::new(OFFERER)
+            .offer_erc20(USDC, U256::from(1500000000_u64))
+            .consideration_item(ConsiderationItem {
+                itemType: ItemType::NATIVE as u8,
+                token: WETH,
+                identifierOrCriteria: U256::ZERO,
+                startAmount: U256::from(1),
+                endAmount: U256::from(1),
+                recipient: OFFERER,
+            })
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn nft_for_token_order() {
+        let nft = address!("5180db8F5c931aaE63c74266b211F580155ecac8");
+        let order = OrderBuilder::new(OFFERER)
+            .offer_erc721(nft, U256::from(1234))
+            .consideration_erc20(WETH, U256::from(1000000000000000000_u128))
+            .build()
+            .unwrap();
+        assert_eq!(order.offer[0].itemType, ItemType::ERC721 as u8);
+        assert_eq!(order.offer[0].identifierOrCriteria, U256::from(1234));
+        assert_eq!(order.offer[0].startAmount, U256::from(1));
+
+        let order = OrderBuilder::new(OFFERER)
+            .offer_erc1155(nft, U256::from(7), U256::from(10))
+            .consideration_erc20(WETH, U256::from(1000000000000000000_u128))
+            .build()
+            .unwrap();
+        assert_eq!(order.offer[0].itemType, ItemType::ERC1155 as u8);
+        assert_eq!(order.offer[0].startAmount, U256::from(10));
+    }
+
+    #[test]
+    fn token_for_nft_criteria_order() {
+        let nft = address!("5180db8F5c931aaE63c74266b211F580155ecac8");
+        let ids = [U256::from(1), U256::from(2), U256::from(3)];
+        let root = crate::criteria::criteria_root(&ids);
+        let order = OrderBuilder::new(OFFERER)
+            .offer_erc20(WETH, U256::from(1000000000000000000_u128))
+            .consideration_erc721_criteria(nft, root)
+            .build()
+            .unwrap();
+        let item = &order.consideration[0];
+        assert_eq!(item.itemType, ItemType::ERC721_WITH_CRITERIA as u8);
+        assert_eq!(item.identifierOrCriteria, root);
+        assert_eq!(item.recipient, OFFERER);
+    }
+}
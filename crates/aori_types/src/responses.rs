@@ -1,14 +1,303 @@
-use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
 
-use crate::events::OrderCreatedData;
+use alloy_primitives::B256;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
 
+use crate::{
+    events::{OrderCreatedData, OrderCreationData},
+    serde_utils::{option_string, string},
+};
+
+/// The reply to an `aori_*` request: the id the request was sent with and the method's
+/// result.
 #[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
-pub struct AoriViewOrderbookResponse {
+pub struct AoriMethodResponse<T> {
     pub id: Option<u64>,
-    pub result: AoriOrderbookData,
+    pub result: T,
 }
 
+/// `aori_ping`, answered with `"aori_pong"`.
+pub type AoriPingResponse = AoriMethodResponse<String>;
+pub type AoriAuthWalletResponse = AoriMethodResponse<AoriAuthData>;
+/// `aori_checkAuth`, answered with whether the JWT is still valid.
+pub type AoriCheckAuthResponse = AoriMethodResponse<bool>;
+pub type AoriViewOrderbookResponse = AoriMethodResponse<AoriOrderbookData>;
+pub type AoriMakeOrderResponse = AoriMethodResponse<AoriOrderHashData>;
+pub type AoriTakeOrderResponse = AoriMethodResponse<AoriOrderHashData>;
+pub type AoriCancelOrderResponse = AoriMethodResponse<AoriOrderHashData>;
+/// `aori_subscribeOrderbook`, answered with a confirmation message. Updates then arrive on
+/// the feed as `events::AoriResponse`.
+pub type AoriSubscribeOrderbookResponse = AoriMethodResponse<String>;
+pub type AoriAccountOrdersResponse = AoriMethodResponse<AoriAccountOrdersData>;
+pub type AoriOrderStatusResponse = AoriMethodResponse<AoriOrderStatusData>;
+
 #[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
 pub struct AoriOrderbookData {
     pub orders: Vec<OrderCreatedData>,
 }
+
+// the JWT `aori_checkAuth` and authenticated requests expect
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
+pub struct AoriAuthData {
+    pub auth: String,
+}
+
+// The order a make, take or cancel request acted on. Whatever else the server sends back
+// ends up in `extra`.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+pub struct AoriOrderHashData {
+    #[serde(rename = "orderHash", with = "string")]
+    pub order_hash: B256,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, Value>,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+pub struct AoriAccountOrdersData {
+    pub orders: Vec<AoriAccountOrder>,
+}
+
+// One of the wallet's orders, as `aori_accountOrders` lists them.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+pub struct AoriAccountOrder {
+    #[serde(flatten)]
+    pub order: OrderCreatedData,
+    #[serde(default, with = "option_string")]
+    pub status: Option<OrderStatus>,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+pub struct AoriOrderStatusData {
+    #[serde(rename = "orderHash", with = "string")]
+    pub order_hash: B256,
+    #[serde(with = "string")]
+    pub status: OrderStatus,
+    #[serde(default)]
+    pub order: Option<OrderCreationData>,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, Value>,
+}
+
+/// Where an order stands on the Aori server.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub enum OrderStatus {
+    /// On the orderbook and open to takers.
+    Active,
+    /// Matched with a taker and waiting to be settled.
+    Taken,
+    /// Settled on-chain.
+    Fulfilled,
+    Cancelled,
+    Expired,
+    /// Any status this version doesn't know about, as received.
+    Unknown(String),
+}
+
+impl OrderStatus {
+    /// Whether the order can no longer change.
+    pub fn is_final(&self) -> bool {
+        matches!(self, Self::Fulfilled | Self::Cancelled | Self::Expired)
+    }
+}
+
+impl FromStr for OrderStatus {
+    type Err = std::convert::Infallible;
+
+    // statuses are matched exactly; any other spelling is kept as `Unknown`
+    fn from_str(status: &str) -> Result<Self, Self::Err> {
+        Ok(match status {
+            "Active" => Self::Active,
+            "Taken" => Self::Taken,
+            "Fulfilled" => Self::Fulfilled,
+            "Cancelled" => Self::Cancelled,
+            "Expired" => Self::Expired,
+            _ => Self::Unknown(status.to_string()),
+        })
+    }
+}
+
+impl fmt::Display for OrderStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Active => f.write_str("Active"),
+            Self::Taken => f.write_str("Taken"),
+            Self::Fulfilled => f.write_str("Fulfilled"),
+            Self::Cancelled => f.write_str("Cancelled"),
+            Self::Expired => f.write_str("Expired"),
+            Self::Unknown(status) => f.write_str(status),
+        }
+    }
+}
+
+/// What the server sends back instead of a result when a request fails.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
+pub struct AoriError {
+    pub code: i64,
+    pub message: String,
+}
+
+/// Parses the reply to an `aori_*` request, turning an error reply into an `Err` that
+/// carries the server's message.
+pub fn parse_response<T: DeserializeOwned>(text: &str) -> eyre::Result<AoriMethodResponse<T>> {
+    let mut response: Value = serde_json::from_str(text)?;
+    if let Some(error) = response.get_mut("error").map(Value::take) {
+        let error: AoriError = serde_json::from_value(error)?;
+        return Err(eyre::eyre!(
+            "aori: request failed with {} ({}).",
+            error.message,
+            error.code
+        ));
+    }
+    Ok(serde_json::from_value(response)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::OrderParameters;
+    use alloy_primitives::{address, U256};
+
+    const ORDER: &str = r#"{
+        "order": {
+            "parameters": {
+                "offerer": "0x8f6E1A8A4D0ee1F8e0ff2e6b9b3B8b5d4A79E1C2",
+                "zone": "0xeA2b4e7F02b859305093f9F4778a19D66CA176d5",
+                "offer": [{
+                    "itemType": 1,
+                    "token": "0x2715Ccea428F8c7694f7e78B2C89cb454c5F7294",
+                    "identifierOrCriteria": "0",
+                    "startAmount": "1000000000000000000",
+                    "endAmount": "1000000000000000000"
+                }],
+                "consideration": [{
+                    "itemType": 1,
+                    "token": "0xD3664B5e72B46eaba722aB6f43c22dBF40181954",
+                    "identifierOrCriteria": "0",
+                    "startAmount": "1500000000",
+                    "endAmount": "1500000000",
+                    "recipient": "0x8f6E1A8A4D0ee1F8e0ff2e6b9b3B8b5d4A79E1C2"
+                }],
+                "orderType": 3,
+                "startTime": "1697240202",
+                "endTime": "1697326602",
+                "zoneHash": "0x0000000000000000000000000000000000000000000000000000000000000000",
+                "salt": "0x2a",
+                "conduitKey": "0x0000000000000000000000000000000000000000000000000000000000000000",
+                "totalOriginalConsiderationItems": 1
+            },
+            "signature": "0x"
+        },
+        "orderHash": "0x0000000000000000000000000000000000000000000000000000000000000000",
+        "inputToken": "0x2715Ccea428F8c7694f7e78B2C89cb454c5F7294",
+        "outputToken": "0xD3664B5e72B46eaba722aB6f43c22dBF40181954",
+        "inputAmount": "1000000000000000000",
+        "outputAmount": "1500000000",
+        "chainId": 5,
+        "active": true,
+        "createdAt": 1697240202,
+        "lastUpdatedAt": 1697240202,
+        "isPublic": true,
+        "rate": null
+    }"#;
+
+    // `ORDER` with the hash it has when signed at counter 3
+    fn signed_order() -> (String, B256) {
+        let mut order: Value = serde_json::from_str(ORDER).unwrap();
+        let parameters: OrderParameters =
+            serde_json::from_value(order["order"]["parameters"].clone()).unwrap();
+        let order_hash = parameters.order_hash(U256::from(3)).unwrap();
+        order["orderHash"] = order_hash.to_string().into();
+        (order.to_string(), order_hash)
+    }
+
+    #[test]
+    fn session_responses() {
+        let pong: AoriPingResponse = parse_response(r#"{"id":1,"result":"aori_pong"}"#).unwrap();
+        assert_eq!(pong.result, "aori_pong");
+
+        let auth: AoriAuthWalletResponse =
+            parse_response(r#"{"id":2,"result":{"auth":"eyJhbGciOiJIUzI1NiJ9.e30.sig"}}"#).unwrap();
+        assert_eq!(auth.id, Some(2));
+        assert_eq!(auth.result.auth, "eyJhbGciOiJIUzI1NiJ9.e30.sig");
+
+        let authed: AoriCheckAuthResponse = parse_response(r#"{"id":3,"result":true}"#).unwrap();
+        assert!(authed.result);
+
+        let subscribed: AoriSubscribeOrderbookResponse =
+            parse_response(r#"{"id":4,"result":"Subscribed to orderbook updates"}"#).unwrap();
+        assert_eq!(subscribed.result, "Subscribed to orderbook updates");
+    }
+
+    #[test]
+    fn order_responses() {
+        let (order, order_hash) = signed_order();
+        let made: AoriMakeOrderResponse = parse_response(&format!(
+            r#"{{"id":5,"result":{{"orderHash":"{}","isPublic":true}}}}"#,
+            order_hash
+        ))
+        .unwrap();
+        assert_eq!(made.result.order_hash, order_hash);
+        assert_eq!(made.result.extra["isPublic"], true);
+
+        let orderbook: AoriViewOrderbookResponse =
+            parse_response(&format!(r#"{{"id":6,"result":{{"orders":[{}]}}}}"#, order)).unwrap();
+        assert_eq!(orderbook.result.orders[0].order_hash, order_hash);
+        assert_eq!(
+            orderbook.result.orders[0].input_amount,
+            U256::from(1000000000000000000_u64)
+        );
+
+        let mut listed: Value = serde_json::from_str(&order).unwrap();
+        listed["status"] = "Active".into();
+        let account: AoriAccountOrdersResponse =
+            parse_response(&format!(r#"{{"id":7,"result":{{"orders":[{}]}}}}"#, listed)).unwrap();
+        let order = &account.result.orders[0];
+        assert_eq!(order.status, Some(OrderStatus::Active));
+        assert_eq!(
+            order.order.order.parameters.offerer,
+            address!("8f6E1A8A4D0ee1F8e0ff2e6b9b3B8b5d4A79E1C2")
+        );
+        assert!(order.order.verify_order_hash(U256::from(3)));
+    }
+
+    #[test]
+    fn order_status_responses() {
+        let (order, order_hash) = signed_order();
+        let status: AoriOrderStatusResponse = parse_response(&format!(
+            r#"{{"id":8,"result":{{"orderHash":"{}","status":"Fulfilled","transactionHash":"0x01"}}}}"#,
+            order_hash
+        ))
+        .unwrap();
+        assert_eq!(status.result.status, OrderStatus::Fulfilled);
+        assert!(status.result.status.is_final());
+        assert!(status.result.order.is_none());
+        assert_eq!(status.result.extra["transactionHash"], "0x01");
+        assert_eq!(
+            "fulfilled".parse::<OrderStatus>().unwrap(),
+            OrderStatus::Unknown("fulfilled".to_string())
+        );
+
+        let status: AoriOrderStatusResponse = parse_response(&format!(
+            r#"{{"id":9,"result":{{"orderHash":"{}","status":"settling","order":{}}}}}"#,
+            order_hash,
+            serde_json::from_str::<Value>(&order).unwrap()["order"]
+        ))
+        .unwrap();
+        assert_eq!(
+            status.result.status,
+            OrderStatus::Unknown("settling".to_string())
+        );
+        assert_eq!(status.result.order.unwrap().signature, "0x");
+    }
+
+    #[test]
+    fn error_responses() {
+        let error = parse_response::<AoriOrderHashData>(
+            r#"{"id":10,"error":{"code":-32000,"message":"Order not found"}}"#,
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("Order not found"));
+        assert!(parse_response::<bool>(r#"{"id":11,"result":"yes"}"#).is_err());
+    }
+}
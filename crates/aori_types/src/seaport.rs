@@ -1,24 +1,15 @@
-use alloy_sol_macro::sol;
+use alloy_primitives::{hex, Address, B256, U256};
 
-use alloy_sol_types::{eip712_domain, Eip712Domain};
+use alloy_sol_macro::sol;
 
-use once_cell::sync::Lazy;
+use alloy_sol_types::{SolCall, SolEvent, SolStruct};
 
 use serde_json::{json, Value};
 
-use crate::constants::{CURRENT_SEAPORT_ADDRESS, CURRENT_SEAPORT_VERSION};
-
-pub static SEAPORT_DOMAIN: Lazy<Eip712Domain> = Lazy::new(|| {
-    eip712_domain! {
-        name: String::from("Seaport"),
-        version: String::from(CURRENT_SEAPORT_VERSION),
-        chain_id: 5,
-        verifying_contract: CURRENT_SEAPORT_ADDRESS,
-    }
-});
+use crate::{auction::current_amount, fill::exact_fraction_of};
 
 sol! {
-    #[derive(Debug)]
+    #[derive(Debug, PartialEq, Eq)]
     enum OrderType {
         FULL_OPEN,
         PARTIAL_OPEN,
@@ -27,7 +18,7 @@ sol! {
         CONTRACT
     }
 
-    #[derive(Debug)]
+    #[derive(Debug, PartialEq, Eq)]
     enum ItemType {
         NATIVE,
         ERC20,
@@ -162,6 +153,21 @@ sol! {
         uint256 amount;
     }
 
+    #[derive(Debug)]
+    enum Side {
+        OFFER,
+        CONSIDERATION
+    }
+
+    #[derive(Debug)]
+    struct CriteriaResolver {
+        uint256 orderIndex;
+        Side side;
+        uint256 index;
+        uint256 identifier;
+        bytes32[] criteriaProof;
+    }
+
     #[derive(Debug)]
     struct AdditionalRecipient {
         uint256 amount;
@@ -195,9 +201,346 @@ sol! {
         ERC1155_TO_ERC20_FULL_RESTRICTED,
         ERC1155_TO_ERC20_PARTIAL_RESTRICTED
     }
+
+    #[derive(Debug)]
+    struct FulfillmentComponent {
+        uint256 orderIndex;
+        uint256 itemIndex;
+    }
+
+    #[derive(Debug)]
+    struct Fulfillment {
+        FulfillmentComponent[] offerComponents;
+        FulfillmentComponent[] considerationComponents;
+    }
+
+    #[derive(Debug)]
+    struct Execution {
+        ReceivedItem item;
+        address offerer;
+        bytes32 conduitKey;
+    }
+
+    #[derive(Debug)]
+    function fulfillOrder(
+        Order calldata order,
+        bytes32 fulfillerConduitKey
+    ) external payable returns (bool fulfilled);
+
+    #[derive(Debug)]
+    function fulfillAdvancedOrder(
+        AdvancedOrder calldata advancedOrder,
+        CriteriaResolver[] calldata criteriaResolvers,
+        bytes32 fulfillerConduitKey,
+        address recipient
+    ) external payable returns (bool fulfilled);
+
+    #[derive(Debug)]
+    function fulfillAvailableAdvancedOrders(
+        AdvancedOrder[] calldata advancedOrders,
+        CriteriaResolver[] calldata criteriaResolvers,
+        FulfillmentComponent[][] calldata offerFulfillments,
+        FulfillmentComponent[][] calldata considerationFulfillments,
+        bytes32 fulfillerConduitKey,
+        address recipient,
+        uint256 maximumFulfilled
+    ) external payable returns (bool[] memory availableOrders, Execution[] memory executions);
+
+    #[derive(Debug)]
+    function matchOrders(
+        Order[] calldata orders,
+        Fulfillment[] calldata fulfillments
+    ) external payable returns (Execution[] memory executions);
+
+    #[derive(Debug)]
+    function matchAdvancedOrders(
+        AdvancedOrder[] calldata orders,
+        CriteriaResolver[] calldata criteriaResolvers,
+        Fulfillment[] calldata fulfillments,
+        address recipient
+    ) external payable returns (Execution[] memory executions);
+
+    #[derive(Debug)]
+    function validate(Order[] calldata orders) external returns (bool validated);
+
+    #[derive(Debug)]
+    function cancel(OrderComponents[] calldata orders) external returns (bool cancelled);
+
+    #[derive(Debug)]
+    function incrementCounter() external returns (uint256 newCounter);
+
+    #[derive(Debug)]
+    function getCounter(address offerer) external view returns (uint256 counter);
+
+    #[derive(Debug)]
+    function getOrderStatus(bytes32 orderHash) external view returns (
+        bool isValidated,
+        bool isCancelled,
+        uint256 totalFilled,
+        uint256 totalSize
+    );
+
+    #[derive(Debug)]
+    function getOrderHash(OrderComponents calldata order) external view returns (bytes32 orderHash);
+
+    // implemented by zones rather than Seaport; restricted orders are only filled if it
+    // returns its own selector
+    #[derive(Debug)]
+    function validateOrder(
+        ZoneParameters calldata zoneParameters
+    ) external returns (bytes4 validOrderMagicValue);
+
+    #[derive(Debug)]
+    event OrderFulfilled(
+        bytes32 orderHash,
+        address indexed offerer,
+        address indexed zone,
+        address recipient,
+        SpentItem[] offer,
+        ReceivedItem[] consideration
+    );
+
+    #[derive(Debug)]
+    event OrderCancelled(bytes32 orderHash, address indexed offerer, address indexed zone);
+
+    #[derive(Debug)]
+    event CounterIncremented(uint256 newCounter, address indexed offerer);
+}
+
+// calldata for Seaport's `cancel`, which must be sent by the offerer (or the zone) of every order
+pub fn encode_cancel(orders: Vec<OrderComponents>) -> Vec<u8> {
+    cancelCall { orders }.abi_encode()
+}
+
+// calldata for Seaport's `fulfillAdvancedOrder`; a zero recipient means the sender
+pub fn encode_fulfill_advanced_order(
+    advanced_order: AdvancedOrder,
+    criteria_resolvers: Vec<CriteriaResolver>,
+    fulfiller_conduit_key: B256,
+    recipient: Address,
+) -> Vec<u8> {
+    fulfillAdvancedOrderCall {
+        advancedOrder: advanced_order,
+        criteriaResolvers: criteria_resolvers,
+        fulfillerConduitKey: fulfiller_conduit_key,
+        recipient,
+    }
+    .abi_encode()
+}
+
+// calldata for Seaport's `incrementCounter`, which invalidates every order signed at the sender's
+// current counter
+pub fn encode_increment_counter() -> Vec<u8> {
+    incrementCounterCall {}.abi_encode()
+}
+
+// calldata for Seaport's `fulfillOrder`, which fills the whole order for the sender
+pub fn encode_fulfill_order(order: Order, fulfiller_conduit_key: B256) -> Vec<u8> {
+    fulfillOrderCall {
+        order,
+        fulfillerConduitKey: fulfiller_conduit_key,
+    }
+    .abi_encode()
+}
+
+// calldata for Seaport's `fulfillAvailableAdvancedOrders`, which fills up to `maximum_fulfilled`
+// of the orders and skips the ones that can no longer be filled
+pub fn encode_fulfill_available_advanced_orders(
+    advanced_orders: Vec<AdvancedOrder>,
+    criteria_resolvers: Vec<CriteriaResolver>,
+    offer_fulfillments: Vec<Vec<FulfillmentComponent>>,
+    consideration_fulfillments: Vec<Vec<FulfillmentComponent>>,
+    fulfiller_conduit_key: B256,
+    recipient: Address,
+    maximum_fulfilled: U256,
+) -> Vec<u8> {
+    fulfillAvailableAdvancedOrdersCall {
+        advancedOrders: advanced_orders,
+        criteriaResolvers: criteria_resolvers,
+        offerFulfillments: offer_fulfillments,
+        considerationFulfillments: consideration_fulfillments,
+        fulfillerConduitKey: fulfiller_conduit_key,
+        recipient,
+        maximumFulfilled: maximum_fulfilled,
+    }
+    .abi_encode()
+}
+
+// calldata for Seaport's `matchOrders`, which pays each order's consideration out of the others'
+// offers as `fulfillments` describes
+pub fn encode_match_orders(orders: Vec<Order>, fulfillments: Vec<Fulfillment>) -> Vec<u8> {
+    matchOrdersCall {
+        orders,
+        fulfillments,
+    }
+    .abi_encode()
+}
+
+// calldata for Seaport's `matchAdvancedOrders`; unspent offer items go to `recipient`, or the
+// sender if it is zero
+pub fn encode_match_advanced_orders(
+    orders: Vec<AdvancedOrder>,
+    criteria_resolvers: Vec<CriteriaResolver>,
+    fulfillments: Vec<Fulfillment>,
+    recipient: Address,
+) -> Vec<u8> {
+    matchAdvancedOrdersCall {
+        orders,
+        criteriaResolvers: criteria_resolvers,
+        fulfillments,
+        recipient,
+    }
+    .abi_encode()
+}
+
+// calldata for Seaport's `validate`, which stores the orders' signatures on-chain so they can be
+// filled without them
+pub fn encode_validate(orders: Vec<Order>) -> Vec<u8> {
+    validateCall { orders }.abi_encode()
+}
+
+// calldata for Seaport's `getCounter`
+pub fn encode_get_counter(offerer: Address) -> Vec<u8> {
+    getCounterCall { offerer }.abi_encode()
+}
+
+// calldata for Seaport's `getOrderStatus`
+pub fn encode_get_order_status(order_hash: B256) -> Vec<u8> {
+    getOrderStatusCall {
+        orderHash: order_hash,
+    }
+    .abi_encode()
+}
+
+// calldata for Seaport's `getOrderHash`
+pub fn encode_get_order_hash(order: OrderComponents) -> Vec<u8> {
+    getOrderHashCall { order }.abi_encode()
+}
+
+// calldata for a zone's `validateOrder`, as Seaport calls it after a restricted order is filled
+pub fn encode_validate_order(zone_parameters: ZoneParameters) -> Vec<u8> {
+    validateOrderCall {
+        zoneParameters: zone_parameters,
+    }
+    .abi_encode()
+}
+
+// the `ZoneParameters` out of a zone's `validateOrder` calldata
+pub fn decode_validate_order(calldata: &[u8]) -> eyre::Result<ZoneParameters> {
+    Ok(validateOrderCall::abi_decode(calldata, true)
+        .map_err(|e| eyre::eyre!("validateOrder: {}", e))?
+        .zoneParameters)
+}
+
+pub fn decode_get_counter(data: &[u8]) -> eyre::Result<U256> {
+    Ok(getCounterCall::abi_decode_returns(data, true)
+        .map_err(|e| eyre::eyre!("getCounter: {}", e))?
+        .counter)
+}
+
+pub fn decode_get_order_status(data: &[u8]) -> eyre::Result<getOrderStatusReturn> {
+    getOrderStatusCall::abi_decode_returns(data, true)
+        .map_err(|e| eyre::eyre!("getOrderStatus: {}", e))
+}
+
+pub fn decode_get_order_hash(data: &[u8]) -> eyre::Result<B256> {
+    Ok(getOrderHashCall::abi_decode_returns(data, true)
+        .map_err(|e| eyre::eyre!("getOrderHash: {}", e))?
+        .orderHash)
+}
+
+// the transfers a `matchOrders` or `matchAdvancedOrders` call made, from its return data
+pub fn decode_executions(data: &[u8]) -> eyre::Result<Vec<Execution>> {
+    Ok(matchOrdersCall::abi_decode_returns(data, true)
+        .map_err(|e| eyre::eyre!("executions: {}", e))?
+        .executions)
+}
+
+/// A decoded call to one of the Seaport functions bound here.
+#[derive(Debug)]
+pub enum SeaportCall {
+    FulfillOrder(fulfillOrderCall),
+    FulfillAdvancedOrder(fulfillAdvancedOrderCall),
+    FulfillAvailableAdvancedOrders(fulfillAvailableAdvancedOrdersCall),
+    MatchOrders(matchOrdersCall),
+    MatchAdvancedOrders(matchAdvancedOrdersCall),
+    Validate(validateCall),
+    Cancel(cancelCall),
+    IncrementCounter(incrementCounterCall),
+    GetCounter(getCounterCall),
+    GetOrderStatus(getOrderStatusCall),
+    GetOrderHash(getOrderHashCall),
+}
+
+impl SeaportCall {
+    /// Decodes transaction calldata by its selector.
+    pub fn decode(calldata: &[u8]) -> eyre::Result<Self> {
+        fn decode<C: SolCall>(calldata: &[u8]) -> eyre::Result<C> {
+            C::abi_decode(calldata, true).map_err(|e| eyre::eyre!("{}: {}", C::SIGNATURE, e))
+        }
+
+        let selector: [u8; 4] = calldata
+            .get(..4)
+            .and_then(|selector| selector.try_into().ok())
+            .ok_or(eyre::eyre!("calldata: too short for a selector."))?;
+        Ok(match selector {
+            fulfillOrderCall::SELECTOR => Self::FulfillOrder(decode(calldata)?),
+            fulfillAdvancedOrderCall::SELECTOR => Self::FulfillAdvancedOrder(decode(calldata)?),
+            fulfillAvailableAdvancedOrdersCall::SELECTOR => {
+                Self::FulfillAvailableAdvancedOrders(decode(calldata)?)
+            }
+            matchOrdersCall::SELECTOR => Self::MatchOrders(decode(calldata)?),
+            matchAdvancedOrdersCall::SELECTOR => Self::MatchAdvancedOrders(decode(calldata)?),
+            validateCall::SELECTOR => Self::Validate(decode(calldata)?),
+            cancelCall::SELECTOR => Self::Cancel(decode(calldata)?),
+            incrementCounterCall::SELECTOR => Self::IncrementCounter(decode(calldata)?),
+            getCounterCall::SELECTOR => Self::GetCounter(decode(calldata)?),
+            getOrderStatusCall::SELECTOR => Self::GetOrderStatus(decode(calldata)?),
+            getOrderHashCall::SELECTOR => Self::GetOrderHash(decode(calldata)?),
+            _ => {
+                return Err(eyre::eyre!(
+                    "calldata: unknown selector 0x{}.",
+                    hex::encode(selector)
+                ))
+            }
+        })
+    }
+}
+
+/// A decoded Seaport event log.
+#[derive(Debug)]
+pub enum SeaportEvent {
+    OrderFulfilled(OrderFulfilled),
+    OrderCancelled(OrderCancelled),
+    CounterIncremented(CounterIncremented),
+}
+
+impl SeaportEvent {
+    /// Decodes a log emitted by Seaport. Returns `None` for events not bound here, such as
+    /// `OrderValidated`.
+    pub fn decode(topics: &[B256], data: &[u8]) -> eyre::Result<Option<Self>> {
+        fn decode<E: SolEvent>(topics: &[B256], data: &[u8]) -> eyre::Result<E> {
+            E::decode_raw_log(topics.iter().copied(), data, true)
+                .map_err(|e| eyre::eyre!("{}: {}", E::SIGNATURE, e))
+        }
+
+        let Some(signature) = topics.first() else {
+            return Ok(None);
+        };
+        Ok(Some(match *signature {
+            OrderFulfilled::SIGNATURE_HASH => Self::OrderFulfilled(decode(topics, data)?),
+            OrderCancelled::SIGNATURE_HASH => Self::OrderCancelled(decode(topics, data)?),
+            CounterIncremented::SIGNATURE_HASH => Self::CounterIncremented(decode(topics, data)?),
+            _ => return Ok(None),
+        }))
+    }
 }
 
 impl OfferItem {
+    pub fn is_native(&self) -> bool {
+        self.itemType == ItemType::NATIVE as u8
+    }
+
     pub fn to_json(&self) -> Value {
         json!({
             "itemType": self.itemType,
@@ -210,6 +553,10 @@ impl OfferItem {
 }
 
 impl ConsiderationItem {
+    pub fn is_native(&self) -> bool {
+        self.itemType == ItemType::NATIVE as u8
+    }
+
     pub fn to_json(&self) -> Value {
         json!({
             "itemType": self.itemType,
@@ -240,7 +587,141 @@ impl OrderParameters {
     }
 }
 
+impl OrderParameters {
+    // pairs the parameters with the offerer's counter, which is what gets signed
+    pub fn to_order_components(&self, counter: U256) -> OrderComponents {
+        OrderComponents {
+            offerer: self.offerer,
+            zone: self.zone,
+            offer: self.offer.clone(),
+            consideration: self.consideration.clone(),
+            orderType: self.orderType as u8,
+            startTime: self.startTime,
+            endTime: self.endTime,
+            zoneHash: self.zoneHash,
+            salt: self.salt,
+            conduitKey: self.conduitKey,
+            counter,
+        }
+    }
+
+    pub fn order_hash(&self, counter: U256) -> B256 {
+        self.to_order_components(counter).order_hash()
+    }
+}
+
 impl OrderComponents {
+    /// Computes the hash Seaport's `getOrderHash` returns for these components, i.e. the
+    /// EIP-712 struct hash (not the signing digest, which also commits to the domain).
+    pub fn order_hash(&self) -> B256 {
+        self.eip712_hash_struct()
+    }
+
+    // drops the counter; the consideration items are taken to be the ones originally signed
+    pub fn to_order_parameters(&self) -> eyre::Result<OrderParameters> {
+        Ok(OrderParameters {
+            offerer: self.offerer,
+            zone: self.zone,
+            offer: self.offer.clone(),
+            consideration: self.consideration.clone(),
+            orderType: OrderType::try_from(self.orderType)
+                .map_err(|e| eyre::eyre!("orderType: {}", e))?,
+            startTime: self.startTime,
+            endTime: self.endTime,
+            zoneHash: self.zoneHash,
+            salt: self.salt,
+            conduitKey: self.conduitKey,
+            totalOriginalConsiderationItems: U256::from(self.consideration.len()),
+        })
+    }
+
+    /// The `ZoneParameters` Seaport hands the order's zone when `fulfiller` takes
+    /// `numerator / denominator` of it at `timestamp`, passing `extra_data` along. Criteria
+    /// items keep their criteria root as the identifier, as nothing has resolved them yet.
+    /// Fails if the fraction doesn't divide every item amount exactly, as Seaport would.
+    pub fn zone_parameters(
+        &self,
+        fulfiller: Address,
+        numerator: u128,
+        denominator: u128,
+        timestamp: u64,
+        extra_data: Vec<u8>,
+        order_hashes: Vec<B256>,
+    ) -> eyre::Result<ZoneParameters> {
+        let amount = |start_amount: U256, end_amount: U256, round_up: bool| {
+            current_amount(
+                exact_fraction_of(start_amount, numerator, denominator)?,
+                exact_fraction_of(end_amount, numerator, denominator)?,
+                self.startTime,
+                self.endTime,
+                timestamp,
+                round_up,
+            )
+        };
+        let item_type = |item_type: u8| {
+            ItemType::try_from(item_type).map_err(|e| eyre::eyre!("itemType: {}", e))
+        };
+
+        Ok(ZoneParameters {
+            orderHash: self.order_hash(),
+            fulfiller,
+            offerer: self.offerer,
+            offer: self
+                .offer
+                .iter()
+                .map(|item| {
+                    Ok(SpentItem {
+                        itemType: item_type(item.itemType)?,
+                        token: item.token,
+                        identifier: item.identifierOrCriteria,
+                        amount: amount(item.startAmount, item.endAmount, false)?,
+                    })
+                })
+                .collect::<eyre::Result<_>>()?,
+            consideration: self
+                .consideration
+                .iter()
+                .map(|item| {
+                    Ok(ReceivedItem {
+                        itemType: item_type(item.itemType)?,
+                        token: item.token,
+                        identifier: item.identifierOrCriteria,
+                        amount: amount(item.startAmount, item.endAmount, true)?,
+                        recipient: item.recipient,
+                    })
+                })
+                .collect::<eyre::Result<_>>()?,
+            extraData: extra_data,
+            orderHashes: order_hashes,
+            startTime: self.startTime,
+            endTime: self.endTime,
+            zoneHash: self.zoneHash,
+        })
+    }
+
+    pub fn to_order(&self, signature: Vec<u8>) -> eyre::Result<Order> {
+        Ok(Order {
+            parameters: self.to_order_parameters()?,
+            signature,
+        })
+    }
+
+    pub fn to_advanced_order(
+        &self,
+        numerator: u128,
+        denominator: u128,
+        signature: Vec<u8>,
+        extra_data: Vec<u8>,
+    ) -> eyre::Result<AdvancedOrder> {
+        Ok(AdvancedOrder {
+            parameters: self.to_order_parameters()?,
+            numerator,
+            denominator,
+            signature,
+            extraData: extra_data,
+        })
+    }
+
     pub fn to_json(&self) -> Value {
         json!({
             "offerer": format!("{}", self.offerer),
@@ -253,22 +734,123 @@ impl OrderComponents {
             "zoneHash": format!("{}", self.zoneHash),
             "salt": format!("{}", self.salt),
             "conduitKey": format!("{}", self.conduitKey),
-            "totalOriginalConsiderationItems": 1,
+            "totalOriginalConsiderationItems": self.consideration.len(),
             "counter": format!("{}", self.counter),
         })
     }
 }
 
+impl AdvancedOrder {
+    pub fn to_json(&self) -> Value {
+        json!({
+            "parameters": self.parameters.to_json(),
+            "numerator": format!("{}", self.numerator),
+            "denominator": format!("{}", self.denominator),
+            "signature": format!("0x{}", hex::encode(&self.signature)),
+            "extraData": format!("0x{}", hex::encode(&self.extraData)),
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::constants::{DEFAULT_CONDUIT_KEY, DEFAULT_ORDER_ADDRESS, DEFAULT_ZONE_HASH};
-    use alloy_primitives::{Address, U256};
+    use crate::{
+        chains::ChainConfig,
+        constants::{DEFAULT_CONDUIT_KEY, DEFAULT_ZONE_HASH},
+    };
+    use alloy_primitives::{address, b256, keccak256, Address, U256};
 
-    #[test]
-    fn load_lazy() {
-        let dom = &*SEAPORT_DOMAIN;
-        println!("{:?}", dom);
+    // the EIP-712 typehashes Seaport signs orders under
+    const OFFER_ITEM_TYPEHASH: B256 =
+        b256!("a66999307ad1bb4fde44d13a5d710bd7718e0c87c1eef68a571629fbf5b93d02");
+    const CONSIDERATION_ITEM_TYPEHASH: B256 =
+        b256!("42d81c6929ffdc4eb27a0808e40e82516ad42296c166065de7f812492304ff6e");
+    const ORDER_TYPEHASH: B256 =
+        b256!("fa445660b7e21515a59617fcd68910b487aa5808b8abda3d78bc85df364b2c2f");
+
+    fn sample_order_components() -> OrderComponents {
+        let offerer = address!("8f6e1a8a4d0ee1f8e0ff2e6b9b3b8b5d4a79e1c2");
+        OrderComponents {
+            offerer,
+            zone: ChainConfig::goerli().zone,
+            offer: vec![OfferItem {
+                itemType: ItemType::ERC20 as u8,
+                token: address!("2715Ccea428F8c7694f7e78B2C89cb454c5F7294"),
+                identifierOrCriteria: U256::ZERO,
+                startAmount: U256::from(1000000000000000000_u128),
+                endAmount: U256::from(1000000000000000000_u128),
+            }],
+            consideration: vec![ConsiderationItem {
+                itemType: ItemType::ERC20 as u8,
+                token: address!("D3664B5e72B46eaba722aB6f43c22dBF40181954"),
+                identifierOrCriteria: U256::ZERO,
+                startAmount: U256::from(1500000000_u64),
+                endAmount: U256::from(1500000000_u64),
+                recipient: offerer,
+            }],
+            orderType: OrderType::PARTIAL_RESTRICTED as u8,
+            startTime: U256::from(1697240202),
+            endTime: U256::from(1697326602),
+            zoneHash: DEFAULT_ZONE_HASH.into(),
+            salt: U256::from(42),
+            conduitKey: DEFAULT_CONDUIT_KEY.into(),
+            counter: U256::from(3),
+        }
+    }
+
+    // mirrors Seaport's `_deriveOrderHash`, word by word
+    fn derive_order_hash(order: &OrderComponents) -> B256 {
+        fn word(value: U256) -> [u8; 32] {
+            value.to_be_bytes::<32>()
+        }
+        fn address_word(value: Address) -> [u8; 32] {
+            let mut out = [0u8; 32];
+            out[12..].copy_from_slice(value.as_slice());
+            out
+        }
+
+        let offer_hashes: Vec<u8> = order
+            .offer
+            .iter()
+            .flat_map(|item| {
+                let mut encoded = OFFER_ITEM_TYPEHASH.to_vec();
+                encoded.extend(word(U256::from(item.itemType)));
+                encoded.extend(address_word(item.token));
+                encoded.extend(word(item.identifierOrCriteria));
+                encoded.extend(word(item.startAmount));
+                encoded.extend(word(item.endAmount));
+                keccak256(encoded).to_vec()
+            })
+            .collect();
+        let consideration_hashes: Vec<u8> = order
+            .consideration
+            .iter()
+            .flat_map(|item| {
+                let mut encoded = CONSIDERATION_ITEM_TYPEHASH.to_vec();
+                encoded.extend(word(U256::from(item.itemType)));
+                encoded.extend(address_word(item.token));
+                encoded.extend(word(item.identifierOrCriteria));
+                encoded.extend(word(item.startAmount));
+                encoded.extend(word(item.endAmount));
+                encoded.extend(address_word(item.recipient));
+                keccak256(encoded).to_vec()
+            })
+            .collect();
+
+        let mut encoded = ORDER_TYPEHASH.to_vec();
+        encoded.extend(address_word(order.offerer));
+        encoded.extend(address_word(order.zone));
+        encoded.extend(keccak256(offer_hashes));
+        encoded.extend(keccak256(consideration_hashes));
+        encoded.extend(word(U256::from(order.orderType)));
+        encoded.extend(word(order.startTime));
+        encoded.extend(word(order.endTime));
+        encoded.extend(order.zoneHash);
+        encoded.extend(word(order.salt));
+        encoded.extend(order.conduitKey);
+        encoded.extend(word(order.counter));
+        keccak256(encoded)
     }
 
     #[test]
@@ -290,7 +872,7 @@ mod tests {
         };
         let order_comps = OrderComponents {
             offerer: Address::ZERO,
-            zone: DEFAULT_ORDER_ADDRESS,
+            zone: ChainConfig::goerli().zone,
             offer: vec![offer_item.clone(), offer_item.clone()],
             consideration: vec![consider_item.clone(), consider_item.clone()],
             orderType: OrderType::PARTIAL_RESTRICTED as u8,
@@ -303,7 +885,7 @@ mod tests {
         };
         let order_params = OrderParameters {
             offerer: Address::ZERO,
-            zone: DEFAULT_ORDER_ADDRESS,
+            zone: ChainConfig::goerli().zone,
             offer: vec![offer_item.clone(), offer_item.clone()],
             consideration: vec![consider_item.clone(), consider_item.clone()],
             orderType: OrderType::PARTIAL_RESTRICTED,
@@ -323,5 +905,296 @@ mod tests {
         assert_eq!(params_json["offer"].as_array().unwrap().len(), 2);
         assert_eq!(comps_json["consideration"].as_array().unwrap().len(), 2);
         assert_eq!(params_json["consideration"].as_array().unwrap().len(), 2);
+        assert_eq!(comps_json["totalOriginalConsiderationItems"], 2);
+    }
+
+    #[test]
+    fn encode_cancel_calls() {
+        let order = sample_order_components();
+        let calldata = encode_cancel(vec![order.clone()]);
+        // cancel((address,address,(uint8,address,uint256,uint256,uint256)[],...)[])
+        assert_eq!(calldata[..4], hex!("fd9f1e10"));
+        let decoded = cancelCall::abi_decode(&calldata, true).unwrap();
+        assert_eq!(decoded.orders.len(), 1);
+        assert_eq!(decoded.orders[0].order_hash(), order.order_hash());
+
+        assert_eq!(encode_increment_counter(), hex!("5b34b966"));
+    }
+
+    #[test]
+    fn seaport_typehashes() {
+        let order = sample_order_components();
+        assert_eq!(order.eip712_type_hash(), ORDER_TYPEHASH);
+        assert_eq!(order.offer[0].eip712_type_hash(), OFFER_ITEM_TYPEHASH);
+        assert_eq!(
+            order.consideration[0].eip712_type_hash(),
+            CONSIDERATION_ITEM_TYPEHASH
+        );
+    }
+
+    // only checks the EIP-712 encoding against a word-by-word one; onchain.rs checks the hash
+    // against Seaport's own `getOrderHash`
+    #[test]
+    fn order_hash_follows_seaport_derivation() {
+        let order = sample_order_components();
+        assert_eq!(order.order_hash(), derive_order_hash(&order));
+    }
+
+    // the worked example of the EIP-712 spec (assets/eip-712/Example.js), hashed through the
+    // same domain and struct hashing orders are signed with
+    #[test]
+    fn eip712_spec_vector() {
+        use alloy_sol_types::{eip712_domain, sol, SolStruct};
+
+        sol! {
+            struct Person {
+                string name;
+                address wallet;
+            }
+
+            struct Mail {
+                Person from;
+                Person to;
+                string contents;
+            }
+        }
+
+        let domain = eip712_domain! {
+            name: "Ether Mail",
+            version: "1",
+            chain_id: 1,
+            verifying_contract: address!("CcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC"),
+        };
+        let mail = Mail {
+            from: Person {
+                name: "Cow".to_string(),
+                wallet: address!("CD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826"),
+            },
+            to: Person {
+                name: "Bob".to_string(),
+                wallet: address!("bBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB"),
+            },
+            contents: "Hello, Bob!".to_string(),
+        };
+        assert_eq!(
+            domain.separator(),
+            b256!("f2cee375fa42b42143804025fc449deafd50cc031ca257e0b194a650a912090f")
+        );
+        assert_eq!(
+            mail.eip712_hash_struct(),
+            b256!("c52c0ee5d84264471806290a3f2c4cecfc5490626bf912d01f240d7a274b371e")
+        );
+        assert_eq!(
+            mail.eip712_signing_hash(&domain),
+            b256!("be609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2")
+        );
+    }
+
+    #[test]
+    fn order_hash_commits_to_counter() {
+        let order = sample_order_components();
+        let mut bumped = order.clone();
+        bumped.counter += U256::from(1);
+        assert_ne!(order.order_hash(), bumped.order_hash());
+    }
+
+    #[test]
+    fn order_parameters_hash_with_counter() {
+        let order = sample_order_components();
+        let params = OrderParameters {
+            offerer: order.offerer,
+            zone: order.zone,
+            offer: order.offer.clone(),
+            consideration: order.consideration.clone(),
+            orderType: OrderType::PARTIAL_RESTRICTED,
+            startTime: order.startTime,
+            endTime: order.endTime,
+            zoneHash: order.zoneHash,
+            salt: order.salt,
+            conduitKey: order.conduitKey,
+            totalOriginalConsiderationItems: U256::from(1),
+        };
+        assert_eq!(params.order_hash(order.counter), order.order_hash());
+    }
+
+    #[test]
+    fn seaport_selectors() {
+        // as listed in Seaport 1.5's ConsiderationInterface
+        assert_eq!(fulfillOrderCall::SELECTOR, hex!("b3a34c4c"));
+        assert_eq!(fulfillAdvancedOrderCall::SELECTOR, hex!("e7acab24"));
+        assert_eq!(
+            fulfillAvailableAdvancedOrdersCall::SELECTOR,
+            hex!("87201b41")
+        );
+        assert_eq!(matchOrdersCall::SELECTOR, hex!("a8174404"));
+        assert_eq!(matchAdvancedOrdersCall::SELECTOR, hex!("f2d12b12"));
+        assert_eq!(validateCall::SELECTOR, hex!("88147732"));
+        assert_eq!(getCounterCall::SELECTOR, hex!("f07ec373"));
+        assert_eq!(getOrderStatusCall::SELECTOR, hex!("46423aa7"));
+        assert_eq!(getOrderHashCall::SELECTOR, hex!("79df72bd"));
+
+        assert_eq!(
+            OrderFulfilled::SIGNATURE_HASH,
+            b256!("9d9af8e38d66c62e2c12f0225249fd9d721c54b83f48d9352c97c6cacdcb6f31")
+        );
+        assert_eq!(
+            OrderCancelled::SIGNATURE_HASH,
+            b256!("6bacc01dbe442496068f7d234edd811f1a5f833243e0aec824f86ab861f3c90d")
+        );
+        assert_eq!(
+            CounterIncremented::SIGNATURE_HASH,
+            b256!("721c20121297512b72821b97f5326877ea8ecf4bb9948fea5bfcb6453074d37f")
+        );
+    }
+
+    #[test]
+    fn decodes_calls_by_selector() {
+        let order = sample_order_components();
+        let signed = order.to_order(vec![1; 65]).unwrap();
+
+        let calldata = encode_match_orders(
+            vec![signed.clone(), signed.clone()],
+            vec![Fulfillment {
+                offerComponents: vec![FulfillmentComponent {
+                    orderIndex: U256::ZERO,
+                    itemIndex: U256::ZERO,
+                }],
+                considerationComponents: vec![FulfillmentComponent {
+                    orderIndex: U256::from(1),
+                    itemIndex: U256::ZERO,
+                }],
+            }],
+        );
+        match SeaportCall::decode(&calldata).unwrap() {
+            SeaportCall::MatchOrders(call) => {
+                assert_eq!(call.orders.len(), 2);
+                assert_eq!(call.orders[1].signature, vec![1; 65]);
+                assert_eq!(
+                    call.fulfillments[0].considerationComponents[0].orderIndex,
+                    U256::from(1)
+                );
+            }
+            call => panic!("decoded {:?}", call),
+        }
+
+        let calldata = encode_get_order_hash(order.clone());
+        match SeaportCall::decode(&calldata).unwrap() {
+            SeaportCall::GetOrderHash(call) => {
+                assert_eq!(call.order.order_hash(), order.order_hash())
+            }
+            call => panic!("decoded {:?}", call),
+        }
+        assert!(matches!(
+            SeaportCall::decode(&encode_validate(vec![signed])).unwrap(),
+            SeaportCall::Validate(_)
+        ));
+        assert!(SeaportCall::decode(&hex!("deadbeef")).is_err());
+        assert!(SeaportCall::decode(&[0xfd]).is_err());
+    }
+
+    #[test]
+    fn decodes_return_data() {
+        let mut status = [0_u8; 128];
+        status[31] = 1;
+        status[95] = 1;
+        status[127] = 4;
+        let status = decode_get_order_status(&status).unwrap();
+        assert!(status.isValidated);
+        assert!(!status.isCancelled);
+        assert_eq!(status.totalFilled, U256::from(1));
+        assert_eq!(status.totalSize, U256::from(4));
+
+        assert_eq!(
+            decode_get_counter(&U256::from(7).to_be_bytes::<32>()).unwrap(),
+            U256::from(7)
+        );
+        let hash = sample_order_components().order_hash();
+        assert_eq!(decode_get_order_hash(hash.as_slice()).unwrap(), hash);
+        assert!(decode_get_counter(&[1, 2, 3]).is_err());
+    }
+
+    #[test]
+    fn decodes_events() {
+        let offerer = address!("f39Fd6e51aad88F6F4ce6aB8827279cffFb92266");
+        let zone = ChainConfig::goerli().zone;
+        let cancelled = OrderCancelled {
+            orderHash: sample_order_components().order_hash(),
+            offerer,
+            zone,
+        };
+        let topics: Vec<B256> = cancelled
+            .encode_topics()
+            .iter()
+            .map(|topic| topic.0)
+            .collect();
+        match SeaportEvent::decode(&topics, &cancelled.encode_data()).unwrap() {
+            Some(SeaportEvent::OrderCancelled(event)) => {
+                assert_eq!(event.orderHash, cancelled.orderHash);
+                assert_eq!(event.offerer, offerer);
+                assert_eq!(event.zone, zone);
+            }
+            event => panic!("decoded {:?}", event),
+        }
+
+        let incremented = CounterIncremented {
+            newCounter: U256::from(3),
+            offerer,
+        };
+        let topics: Vec<B256> = incremented
+            .encode_topics()
+            .iter()
+            .map(|topic| topic.0)
+            .collect();
+        assert!(matches!(
+            SeaportEvent::decode(&topics, &incremented.encode_data()).unwrap(),
+            Some(SeaportEvent::CounterIncremented(CounterIncremented { newCounter, .. }))
+                if newCounter == U256::from(3)
+        ));
+
+        assert!(SeaportEvent::decode(&[B256::ZERO], &[]).unwrap().is_none());
+        assert!(SeaportEvent::decode(&[OrderCancelled::SIGNATURE_HASH], &[]).is_err());
+    }
+
+    #[test]
+    fn zone_parameters_round_trip() {
+        assert_eq!(validateOrderCall::SELECTOR, hex!("17b1f942"));
+
+        let mut order = sample_order_components();
+        order.zoneHash = B256::repeat_byte(0xaa);
+        order.offer[0].startAmount = U256::from(1000);
+        order.offer[0].endAmount = U256::from(1000);
+        order.consideration[0].startAmount = U256::from(3000);
+        order.consideration[0].endAmount = U256::from(3000);
+        let fulfiller = address!("70997970C51812dc3A010C7d01b50e0d17dc79C8");
+        let parameters = order
+            .zone_parameters(
+                fulfiller,
+                1,
+                2,
+                1697240202,
+                vec![0xde, 0xad],
+                vec![order.order_hash()],
+            )
+            .unwrap();
+        assert_eq!(parameters.zoneHash, B256::repeat_byte(0xaa));
+        assert_eq!(parameters.offer[0].amount, U256::from(500));
+        assert_eq!(parameters.consideration[0].amount, U256::from(1500));
+        assert_eq!(
+            parameters.consideration[0].recipient,
+            order.consideration[0].recipient
+        );
+
+        let decoded = decode_validate_order(&encode_validate_order(parameters)).unwrap();
+        assert_eq!(decoded.orderHash, order.order_hash());
+        assert_eq!(decoded.fulfiller, fulfiller);
+        assert_eq!(decoded.extraData, vec![0xde, 0xad]);
+        assert_eq!(decoded.orderHashes, vec![order.order_hash()]);
+        assert!(matches!(decoded.offer[0].itemType, ItemType::ERC20));
+        assert!(decode_validate_order(&encode_get_counter(fulfiller)).is_err());
+
+        order.consideration[0].startAmount = U256::from(3001);
+        assert!(order
+            .zone_parameters(fulfiller, 1, 2, 1697240202, vec![], vec![])
+            .is_err());
     }
 }
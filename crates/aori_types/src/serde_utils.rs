@@ -0,0 +1,299 @@
+use std::{fmt, str::FromStr};
+
+use alloy_primitives::U256;
+use serde::{
+    de::{self, Visitor},
+    Deserializer, Serializer,
+};
+
+/// (De)serializes a `U256` amount for `#[serde(with = "...")]`.
+///
+/// The Aori API reports amounts either as JSON numbers or as strings, so both are accepted on
+/// the way in: numbers, decimal strings and `0x`-prefixed hex strings. Amounts are always
+/// written back out as decimal strings, since JSON numbers can't carry a full `uint256`.
+pub mod u256_amount {
+    use super::*;
+
+    pub fn serialize<S>(amount: &U256, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&amount.to_string())
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<U256, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(U256AmountVisitor)
+    }
+}
+
+/// Like `u256_amount`, for optional fields that may be missing or `null`.
+pub mod option_u256_amount {
+    use super::*;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize)]
+    struct Amount(#[serde(with = "u256_amount")] U256);
+
+    pub fn serialize<S>(amount: &Option<U256>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        amount.map(Amount).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<U256>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Ok(Option::<Amount>::deserialize(deserializer)?.map(|amount| amount.0))
+    }
+}
+
+/// (De)serializes a `U256` as hex, with or without the `0x` prefix, as the Aori API sends
+/// order salts. Written back out `0x`-prefixed.
+pub mod hex_u256 {
+    use super::*;
+    use serde::Deserialize;
+
+    pub fn serialize<S>(value: &U256, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&format!("{:#x}", value))
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<U256, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = String::deserialize(deserializer)?;
+        U256::from_str_radix(value.trim_start_matches("0x"), 16)
+            .map_err(|_| de::Error::custom(format!("invalid hex value {:?}", value)))
+    }
+}
+
+/// (De)serializes anything with `Display` and `FromStr` as a string, such as `Address` and
+/// `B256`. Hex parsing ignores casing, so checksummed and lowercase addresses both work.
+pub mod string {
+    use super::*;
+    use serde::Deserialize;
+
+    pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: fmt::Display,
+        S: Serializer,
+    {
+        serializer.collect_str(value)
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: FromStr,
+        D: Deserializer<'de>,
+    {
+        let value = String::deserialize(deserializer)?;
+        value
+            .parse::<T>()
+            .map_err(|_| de::Error::custom(format!("invalid value {:?}", value)))
+    }
+}
+
+/// Like `string`, for optional fields that may be missing or `null`.
+pub mod option_string {
+    use super::*;
+    use serde::Deserialize;
+
+    pub fn serialize<T, S>(value: &Option<T>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: fmt::Display,
+        S: Serializer,
+    {
+        match value {
+            Some(value) => serializer.collect_str(value),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+    where
+        T: FromStr,
+        D: Deserializer<'de>,
+    {
+        Option::<String>::deserialize(deserializer)?
+            .map(|value| {
+                value
+                    .parse::<T>()
+                    .map_err(|_| de::Error::custom(format!("invalid value {:?}", value)))
+            })
+            .transpose()
+    }
+}
+
+/// (De)serializes a `sol!` enum such as `ItemType` as its number, the way Seaport and the
+/// Aori API write it. Numbers the enum doesn't define are rejected.
+pub mod sol_enum {
+    use super::*;
+    use serde::{Deserialize, Serialize};
+
+    pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Copy + Into<u8>,
+        S: Serializer,
+    {
+        (*value).into().serialize(serializer)
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: TryFrom<u8>,
+        D: Deserializer<'de>,
+    {
+        let value = u8::deserialize(deserializer)?;
+        T::try_from(value).map_err(|_| de::Error::custom(format!("unknown value {}", value)))
+    }
+}
+
+/// 2^53, the largest integer up to which every integer is an `f64`.
+const MAX_EXACT_F64: f64 = 9007199254740992.0;
+
+struct U256AmountVisitor;
+
+impl<'de> Visitor<'de> for U256AmountVisitor {
+    type Value = U256;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a non-negative integer amount as a number or string")
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<U256, E> {
+        Ok(U256::from(value))
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<U256, E> {
+        u64::try_from(value)
+            .map(U256::from)
+            .map_err(|_| E::custom(format!("amount {} is negative", value)))
+    }
+
+    // serde_json parses integers past u64::MAX as floats. Past 2^53 a float can no longer
+    // tell neighbouring integers apart, so the amount may already be off; such amounts have
+    // to be sent as strings.
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<U256, E> {
+        if !(value >= 0.0 && value.fract() == 0.0) {
+            Err(E::custom(format!("amount {} is not a whole number", value)))
+        } else if value > MAX_EXACT_F64 {
+            Err(E::custom(format!(
+                "amount {} is too large to be exact as a number",
+                value
+            )))
+        } else {
+            Ok(U256::from(value as u64))
+        }
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<U256, E> {
+        let parsed = match value.strip_prefix("0x") {
+            Some(hex) => U256::from_str_radix(hex, 16),
+            None => U256::from_str_radix(value, 10),
+        };
+        parsed.map_err(|_| E::custom(format!("invalid amount {:?}", value)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct Amount {
+        #[serde(with = "u256_amount")]
+        amount: U256,
+    }
+
+    fn parse(value: serde_json::Value) -> Result<U256, serde_json::Error> {
+        serde_json::from_value::<Amount>(json!({ "amount": value })).map(|parsed| parsed.amount)
+    }
+
+    #[test]
+    fn accepts_numbers_and_strings() {
+        let wei = U256::from(1000000000000000000_u64);
+        assert_eq!(parse(json!(1000000000000000000_u64)).unwrap(), wei);
+        assert_eq!(parse(json!("1000000000000000000")).unwrap(), wei);
+        assert_eq!(parse(json!("0xde0b6b3a7640000")).unwrap(), wei);
+
+        // 100 WETH doesn't fit in a u64
+        let large = "100000000000000000000";
+        assert_eq!(parse(json!(large)).unwrap(), large.parse::<U256>().unwrap());
+        assert_eq!(
+            parse(json!(9007199254740992.0)).unwrap(),
+            U256::from(1_u64 << 53)
+        );
+    }
+
+    #[test]
+    fn rejects_inexact_numbers() {
+        // parses as 123456789012345683968
+        let raw = r#"{"amount": 123456789012345678901}"#;
+        let err = serde_json::from_str::<Amount>(raw).unwrap_err();
+        assert!(err.to_string().contains("too large to be exact"));
+        assert!(serde_json::from_str::<Amount>(r#"{"amount": 100000000000000000000}"#).is_err());
+        assert!(parse(json!("123456789012345678901")).is_ok());
+    }
+
+    #[test]
+    fn rejects_invalid_amounts() {
+        assert!(parse(json!(-1)).is_err());
+        assert!(parse(json!(1.5)).is_err());
+        assert!(parse(json!("one")).is_err());
+        assert!(parse(json!(null)).is_err());
+    }
+
+    #[test]
+    fn addresses_in_any_casing() {
+        use alloy_primitives::{address, Address};
+
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        struct Token {
+            #[serde(with = "string")]
+            address: Address,
+            #[serde(with = "hex_u256")]
+            salt: U256,
+        }
+
+        let token = Token {
+            address: address!("D3664B5e72B46eaba722aB6f43c22dBF40181954"),
+            salt: U256::from(42),
+        };
+        for (address, salt) in [
+            ("0xD3664B5e72B46eaba722aB6f43c22dBF40181954", "0x2a"),
+            ("0xd3664b5e72b46eaba722ab6f43c22dbf40181954", "2a"),
+        ] {
+            let parsed: Token =
+                serde_json::from_value(json!({ "address": address, "salt": salt })).unwrap();
+            assert_eq!(parsed, token);
+        }
+        assert_eq!(
+            serde_json::to_value(&token).unwrap(),
+            json!({ "address": "0xD3664B5e72B46eaba722aB6f43c22dBF40181954", "salt": "0x2a" })
+        );
+        assert!(
+            serde_json::from_value::<Token>(json!({ "address": "usdc", "salt": "0x2a" })).is_err()
+        );
+    }
+
+    #[test]
+    fn serializes_as_decimal_string() {
+        let amount = Amount {
+            amount: U256::from(1500000000),
+        };
+        assert_eq!(
+            serde_json::to_value(&amount).unwrap(),
+            json!({ "amount": "1500000000" })
+        );
+    }
+}
@@ -0,0 +1,98 @@
+use std::fmt;
+
+use alloy_primitives::U256;
+
+use crate::{
+    events::OrderCreatedData,
+    responses::{AoriOrderStatusData, OrderStatus},
+    seaport::getOrderStatusReturn,
+};
+
+/// How an order the Aori server still shows as active actually stands on Seaport. Such an
+/// order is phantom liquidity: taking it fails, or fills less than it promises.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StatusMismatch {
+    Cancelled,
+    Filled,
+    /// `filled` out of `size` parts of the order are gone.
+    PartiallyFilled {
+        filled: U256,
+        size: U256,
+    },
+}
+
+impl fmt::Display for StatusMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Cancelled => write!(f, "cancelled on-chain"),
+            Self::Filled => write!(f, "filled on-chain"),
+            Self::PartiallyFilled { filled, size } => {
+                write!(f, "{}/{} filled on-chain", filled, size)
+            }
+        }
+    }
+}
+
+/// Compares what the server reports with Seaport's `getOrderStatus`. Only orders the server
+/// shows as active can be out of step in a way that matters to takers.
+pub fn cross_check(active: bool, onchain: &getOrderStatusReturn) -> Option<StatusMismatch> {
+    if !active {
+        return None;
+    }
+    if onchain.isCancelled {
+        Some(StatusMismatch::Cancelled)
+    } else if onchain.totalSize.is_zero() || onchain.totalFilled.is_zero() {
+        None
+    } else if onchain.totalFilled >= onchain.totalSize {
+        Some(StatusMismatch::Filled)
+    } else {
+        Some(StatusMismatch::PartiallyFilled {
+            filled: onchain.totalFilled,
+            size: onchain.totalSize,
+        })
+    }
+}
+
+impl AoriOrderStatusData {
+    pub fn cross_check(&self, onchain: &getOrderStatusReturn) -> Option<StatusMismatch> {
+        cross_check(self.status == OrderStatus::Active, onchain)
+    }
+}
+
+impl OrderCreatedData {
+    pub fn cross_check(&self, onchain: &getOrderStatusReturn) -> Option<StatusMismatch> {
+        cross_check(self.active, onchain)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn onchain(is_cancelled: bool, total_filled: u64, total_size: u64) -> getOrderStatusReturn {
+        getOrderStatusReturn {
+            isValidated: true,
+            isCancelled: is_cancelled,
+            totalFilled: U256::from(total_filled),
+            totalSize: U256::from(total_size),
+        }
+    }
+
+    #[test]
+    fn flags_phantom_liquidity() {
+        assert_eq!(cross_check(true, &onchain(false, 0, 0)), None);
+        assert_eq!(
+            cross_check(true, &onchain(true, 0, 0)),
+            Some(StatusMismatch::Cancelled)
+        );
+        assert_eq!(
+            cross_check(true, &onchain(false, 1, 1)),
+            Some(StatusMismatch::Filled)
+        );
+        let partial = cross_check(true, &onchain(false, 1, 4)).unwrap();
+        assert_eq!(partial.to_string(), "1/4 filled on-chain");
+
+        // the server already knows the order is done
+        assert_eq!(cross_check(false, &onchain(true, 1, 1)), None);
+    }
+}
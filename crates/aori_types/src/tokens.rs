@@ -0,0 +1,308 @@
+use std::{collections::HashMap, path::Path, sync::Arc};
+
+use alloy_primitives::{address, Address, U256};
+use ethers::providers::Middleware;
+use rust_decimal::Decimal;
+use serde::Deserialize;
+
+use crate::IERC20;
+
+/// An ERC20 token on a given chain, with what's needed to show its amounts to people.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TokenInfo {
+    pub chain_id: u64,
+    pub address: Address,
+    pub symbol: String,
+    pub decimals: u8,
+}
+
+impl TokenInfo {
+    pub fn new(chain_id: u64, address: Address, symbol: &str, decimals: u8) -> Self {
+        Self {
+            chain_id,
+            address,
+            symbol: symbol.to_string(),
+            decimals,
+        }
+    }
+
+    /// Converts a raw on-chain amount into whole tokens, e.g. 1500000 USDC units into 1.5.
+    pub fn to_decimal(&self, amount: U256) -> eyre::Result<Decimal> {
+        let mantissa = i128::try_from(amount)
+            .map_err(|_| eyre::eyre!("{}: {} is too large for a decimal.", self.symbol, amount))?;
+        Decimal::try_from_i128_with_scale(mantissa, self.decimals as u32)
+            .map(|value| value.normalize())
+            .map_err(|_| {
+                eyre::eyre!(
+                    "{}: {} with {} decimals does not fit in a decimal.",
+                    self.symbol,
+                    amount,
+                    self.decimals
+                )
+            })
+    }
+
+    /// Converts whole tokens into the raw on-chain amount, e.g. 1.5 USDC into 1500000 units.
+    /// Fails rather than rounding if `value` is more precise than the token.
+    pub fn from_decimal(&self, value: Decimal) -> eyre::Result<U256> {
+        if value.is_sign_negative() && !value.is_zero() {
+            return Err(eyre::eyre!("{}: {} is negative.", self.symbol, value));
+        }
+        let value = value.normalize();
+        let mantissa = U256::from(value.mantissa().unsigned_abs());
+        let (scale, decimals) = (value.scale(), self.decimals as u32);
+        let too_large = || {
+            eyre::eyre!(
+                "{}: {} with {} decimals overflows a uint256.",
+                self.symbol,
+                value,
+                decimals
+            )
+        };
+
+        if scale <= decimals {
+            U256::from(10)
+                .checked_pow(U256::from(decimals - scale))
+                .and_then(|factor| mantissa.checked_mul(factor))
+                .ok_or_else(too_large)
+        } else {
+            let divisor = U256::from(10)
+                .checked_pow(U256::from(scale - decimals))
+                .ok_or_else(too_large)?;
+            if !(mantissa % divisor).is_zero() {
+                return Err(eyre::eyre!(
+                    "{}: {} has more than {} decimals.",
+                    self.symbol,
+                    value,
+                    decimals
+                ));
+            }
+            Ok(mantissa / divisor)
+        }
+    }
+}
+
+// the file format: a bare list of tokens, or a token list with them under `tokens`
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum TokenFile {
+    List(Vec<TokenFileEntry>),
+    TokenList { tokens: Vec<TokenFileEntry> },
+}
+
+#[derive(Deserialize)]
+struct TokenFileEntry {
+    #[serde(rename = "chainId")]
+    chain_id: u64,
+    address: String,
+    symbol: String,
+    decimals: u8,
+}
+
+/// Known tokens, keyed by chain id and address.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TokenRegistry {
+    tokens: HashMap<(u64, Address), TokenInfo>,
+}
+
+impl TokenRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The Goerli test tokens Aori trades against.
+    pub fn goerli() -> Self {
+        Self::new()
+            .with_token(TokenInfo::new(
+                5,
+                address!("2715Ccea428F8c7694f7e78B2C89cb454c5F7294"),
+                "WETH",
+                18,
+            ))
+            .with_token(TokenInfo::new(
+                5,
+                address!("D3664B5e72B46eaba722aB6f43c22dBF40181954"),
+                "USDC",
+                6,
+            ))
+    }
+
+    /// Parses a JSON list of `{ chainId, address, symbol, decimals }` entries. Token lists in
+    /// the common `{ "tokens": [...] }` layout work as they are.
+    pub fn from_json(json: &str) -> eyre::Result<Self> {
+        let entries = match serde_json::from_str::<TokenFile>(json)? {
+            TokenFile::List(tokens) | TokenFile::TokenList { tokens } => tokens,
+        };
+        let mut registry = Self::new();
+        for entry in entries {
+            let address = entry
+                .address
+                .parse::<Address>()
+                .map_err(|_| eyre::eyre!("tokens: invalid address {}.", entry.address))?;
+            registry.insert(TokenInfo::new(
+                entry.chain_id,
+                address,
+                &entry.symbol,
+                entry.decimals,
+            ));
+        }
+        Ok(registry)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> eyre::Result<Self> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn with_token(mut self, token: TokenInfo) -> Self {
+        self.insert(token);
+        self
+    }
+
+    pub fn insert(&mut self, token: TokenInfo) {
+        self.tokens.insert((token.chain_id, token.address), token);
+    }
+
+    pub fn get(&self, chain_id: u64, address: Address) -> Option<&TokenInfo> {
+        self.tokens.get(&(chain_id, address))
+    }
+
+    pub fn contains(&self, chain_id: u64, address: Address) -> bool {
+        self.tokens.contains_key(&(chain_id, address))
+    }
+
+    pub fn by_symbol(&self, chain_id: u64, symbol: &str) -> Option<&TokenInfo> {
+        self.tokens
+            .values()
+            .find(|token| token.chain_id == chain_id && token.symbol.eq_ignore_ascii_case(symbol))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &TokenInfo> {
+        self.tokens.values()
+    }
+
+    pub fn tokens(&self, chain_id: u64) -> impl Iterator<Item = &TokenInfo> {
+        self.tokens
+            .values()
+            .filter(move |token| token.chain_id == chain_id)
+    }
+
+    /// Looks up `address` on the chain behind `client` through its ERC20 `symbol` and
+    /// `decimals`, and registers it. Tokens already known are returned as they are.
+    pub async fn fetch<M: Middleware + 'static>(
+        &mut self,
+        client: Arc<M>,
+        chain_id: u64,
+        address: Address,
+    ) -> eyre::Result<&TokenInfo> {
+        if !self.contains(chain_id, address) {
+            let token = IERC20::new(
+                ethers::types::Address::from_slice(address.as_slice()),
+                client,
+            );
+            let symbol = token.symbol().call().await?;
+            let decimals = token.decimals().call().await?;
+            self.insert(TokenInfo::new(chain_id, address, &symbol, decimals));
+        }
+        Ok(&self.tokens[&(chain_id, address)])
+    }
+
+    pub fn to_decimal(
+        &self,
+        chain_id: u64,
+        address: Address,
+        amount: U256,
+    ) -> eyre::Result<Decimal> {
+        self.known(chain_id, address)?.to_decimal(amount)
+    }
+
+    pub fn from_decimal(
+        &self,
+        chain_id: u64,
+        address: Address,
+        value: Decimal,
+    ) -> eyre::Result<U256> {
+        self.known(chain_id, address)?.from_decimal(value)
+    }
+
+    fn known(&self, chain_id: u64, address: Address) -> eyre::Result<&TokenInfo> {
+        self.get(chain_id, address).ok_or(eyre::eyre!(
+            "tokens: {} is not registered on chain {}.",
+            address,
+            chain_id
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    const WETH: Address = address!("2715Ccea428F8c7694f7e78B2C89cb454c5F7294");
+    const USDC: Address = address!("D3664B5e72B46eaba722aB6f43c22dBF40181954");
+
+    #[test]
+    fn converts_both_ways() {
+        let tokens = TokenRegistry::goerli();
+        assert_eq!(
+            tokens.to_decimal(5, USDC, U256::from(1500000)).unwrap(),
+            dec!(1.5)
+        );
+        assert_eq!(
+            tokens.from_decimal(5, USDC, dec!(1.5)).unwrap(),
+            U256::from(1500000)
+        );
+        assert_eq!(
+            tokens.from_decimal(5, WETH, dec!(0.75)).unwrap(),
+            U256::from(750000000000000000_u64)
+        );
+        assert_eq!(
+            tokens
+                .to_decimal(5, WETH, U256::from(100000000000000000000_u128))
+                .unwrap(),
+            dec!(100)
+        );
+        assert_eq!(
+            tokens.from_decimal(5, USDC, dec!(2.000000000)).unwrap(),
+            U256::from(2000000)
+        );
+    }
+
+    #[test]
+    fn rejects_what_it_cannot_represent() {
+        let tokens = TokenRegistry::goerli();
+        // USDC has no seventh decimal
+        assert!(tokens.from_decimal(5, USDC, dec!(1.0000001)).is_err());
+        assert!(tokens.from_decimal(5, USDC, dec!(-1)).is_err());
+        assert!(tokens.to_decimal(5, WETH, U256::MAX).is_err());
+        // 10^78 units and up are past a uint256
+        let wide = TokenInfo::new(5, WETH, "WIDE", 79);
+        assert!(wide.from_decimal(dec!(1)).is_err());
+        assert!(wide.from_decimal(dec!(0.1)).is_err());
+        // unknown chain
+        assert!(tokens.to_decimal(1, USDC, U256::from(1)).is_err());
+    }
+
+    #[test]
+    fn loads_token_files() {
+        let list = r#"[
+            { "chainId": 1, "address": "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48", "symbol": "USDC", "decimals": 6 }
+        ]"#;
+        let tokens = TokenRegistry::from_json(list).unwrap();
+        let usdc = tokens.by_symbol(1, "usdc").unwrap();
+        assert_eq!(
+            usdc.address,
+            address!("A0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48")
+        );
+        assert_eq!(tokens.tokens(1).count(), 1);
+        assert_eq!(tokens.tokens(5).count(), 0);
+
+        let token_list = format!(r#"{{ "name": "test", "tokens": {} }}"#, list);
+        assert_eq!(TokenRegistry::from_json(&token_list).unwrap(), tokens);
+
+        assert!(TokenRegistry::from_json(
+            r#"[{ "chainId": 1, "address": "0x1", "symbol": "X", "decimals": 6 }]"#
+        )
+        .is_err());
+    }
+}
@@ -8,75 +8,95 @@
 // first implementation only implements weth and usdc test tokens on goerli
 
 use super::types::{Action, Event};
+use alloy_primitives::{hex, Address, U256};
 use anyhow::Error;
-use aori_requests::requests::create_take_order_payload;
+use aori_requests::counters::CounterCache;
+use aori_requests::onchain::{check_fillability, cross_check_order_status};
+use aori_types::chains::ChainRegistry;
 use aori_types::events::{AoriEvent, OrderCreatedData};
+use aori_types::fill::FillAmount;
+use aori_types::matching::OrderMatch;
+use aori_types::seaport::OrderComponents;
+use aori_types::tokens::TokenRegistry;
 use artemis_core::types::Strategy;
 use async_trait::async_trait;
-use ethers::prelude::{k256::ecdsa::SigningKey, Wallet};
+use ethers::providers::{Provider, Ws};
 
 use std::sync::Arc;
-use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
 use tracing::info;
 
-#[derive(Debug, Clone)]
-pub struct TokenEntry<'a> {
-    /// Address of the v2 pool.
-    pub address: &'a str,
-    /// Whether the pool has weth as token0.
-    pub ticker: &'a str,
-    pub chain_id: u64,
-}
-impl<'a> PartialEq for TokenEntry<'a> {
-    fn eq(&self, other: &Self) -> bool {
-        self.address == other.address && self.chain_id == other.chain_id
-    }
-}
-
 #[derive(Debug, Clone)]
 pub struct SimpleArb {
-    pub token_list: Vec<TokenEntry<'static>>,
+    pub chains: ChainRegistry,
+    pub tokens: TokenRegistry,
+    /// Where orders are checked against Seaport; without it, only orders that come with
+    /// their counter can be matched, and on trust.
+    pub node: Option<Provider<Ws>>,
+    pub counters: CounterCache,
     pub orderbook_entries: Vec<OrderCreatedData>,
-    pub wallet: Wallet<SigningKey>,
-    pub last_id: Arc<Mutex<u64>>,
-    pub api_key: String,
+}
+
+impl Default for SimpleArb {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl SimpleArb {
-    /// Create a new instance of the strategy.
-    pub fn new(wallet: Wallet<SigningKey>, last_id: Arc<Mutex<u64>>, api_key: String) -> Self {
-        let _token_list: Vec<TokenEntry> = vec![
-            TokenEntry {
-                address: "0xD3664B5e72B46eaba722aB6f43c22dBF40181954",
-                ticker: "usdc",
-                chain_id: 5,
-            },
-            TokenEntry {
-                address: "0x2715Ccea428F8c7694f7e78B2C89cb454c5F7294",
-                ticker: "weth",
-                chain_id: 5,
-            },
-        ];
-        info!(
-            "relevant token: {} wiht chain id {}",
-            _token_list[0].address, _token_list[0].chain_id
-        );
-        info!(
-            "relevant token: {} with chain id {}",
-            _token_list[1].address, _token_list[1].chain_id
-        );
+    /// Create a new instance of the strategy, trading the Goerli test tokens.
+    pub fn new() -> Self {
         Self {
-            token_list: _token_list,
+            chains: ChainRegistry::presets(),
+            tokens: TokenRegistry::goerli(),
+            node: None,
+            counters: CounterCache::new(),
             orderbook_entries: Vec::new(),
-            wallet,
-            last_id,
-            api_key,
         }
+        .log_tokens()
+    }
+
+    /// Signs against `chains` instead of the built-in presets.
+    pub fn with_chains(mut self, chains: ChainRegistry) -> Self {
+        self.chains = chains;
+        self
     }
-    fn is_token_relevant(&self, token_address: &str, chain_id: u64) -> bool {
-        self.token_list.iter().any(|token_entry| {
-            token_entry.address == token_address && token_entry.chain_id == chain_id
-        })
+
+    /// Checks orders against Seaport through `node`, which must be on the traded chain.
+    pub fn with_node(mut self, node: Provider<Ws>) -> Self {
+        self.node = Some(node);
+        self
+    }
+
+    /// Trades `tokens` instead of the Goerli defaults.
+    pub fn with_tokens(mut self, tokens: TokenRegistry) -> Self {
+        self.tokens = tokens;
+        self.log_tokens()
+    }
+
+    fn log_tokens(self) -> Self {
+        for token in self.tokens.iter() {
+            info!(
+                "relevant token: {} ({}) with chain id {}",
+                token.symbol, token.address, token.chain_id
+            );
+        }
+        self
+    }
+
+    fn is_token_relevant(&self, token_address: Address, chain_id: u64) -> bool {
+        self.tokens.contains(chain_id, token_address)
+    }
+
+    // e.g. "1.5 USDC", falling back to the raw amount for unknown tokens
+    fn format_amount(&self, token_address: Address, chain_id: u64, amount: U256) -> String {
+        self.tokens
+            .get(chain_id, token_address)
+            .and_then(|token| {
+                let value = token.to_decimal(amount).ok()?;
+                Some(format!("{} {}", value, token.symbol))
+            })
+            .unwrap_or_else(|| format!("{} of {}", amount, token_address))
     }
 }
 // don't do anything at startup
@@ -89,6 +109,17 @@ impl Strategy<Event, Action> for SimpleArb {
 
     async fn process_event(&mut self, event: Event) -> Vec<Action> {
         match event {
+            // keeps cached counters current when offerers bump them
+            Event::SeaportLog(chain_id, log) => {
+                let applied = self
+                    .chains
+                    .chain(chain_id)
+                    .and_then(|chain| self.counters.apply_logs(chain, &[*log]));
+                if let Err(err) = applied {
+                    info!("Ignoring Seaport log: {}", err);
+                }
+                vec![]
+            }
             Event::AoriTransaction(aori_event) => {
                 info!("Received a new aori event: {:?}", aori_event);
 
@@ -102,9 +133,9 @@ impl Strategy<Event, Action> for SimpleArb {
 
                         // check if token relevant
                         if self
-                            .is_token_relevant(&order_data.input_token, order_data.chain_id as u64)
+                            .is_token_relevant(order_data.input_token, order_data.chain_id as u64)
                             || self.is_token_relevant(
-                                &order_data.output_token,
+                                order_data.output_token,
                                 order_data.chain_id as u64,
                             )
                         // if it is, look whether there is a trade to be done -> i.e. look for whether there is a matching order
@@ -115,32 +146,59 @@ impl Strategy<Event, Action> for SimpleArb {
                             );
                             self.orderbook_entries.push(order_data.clone());
 
-                            // Look for matching entries with the same input and output token pair
+                            // Look for matching entries with the same input and output token pair,
+                            // priced at the current point of any auction
+                            let now = SystemTime::now()
+                                .duration_since(UNIX_EPOCH)
+                                .map_or(0, |elapsed| elapsed.as_secs());
+                            let wanted_output = match order_data.current_amounts(now) {
+                                Ok((_, output)) => output,
+                                Err(err) => {
+                                    info!(
+                                        "skipping unreadable order {}: {}",
+                                        order_data.order_hash, err
+                                    );
+                                    return vec![];
+                                }
+                            };
                             let mut matching_orders = self
                                 .orderbook_entries
                                 .iter()
                                 .filter(|entry| {
                                     entry.input_token == order_data.output_token
                                         && entry.output_token == order_data.input_token
-                                        && entry.input_amount > order_data.output_amount
+                                })
+                                .filter_map(|entry| {
+                                    let (offered_input, _) = entry.current_amounts(now).ok()?;
+                                    (offered_input > wanted_output)
+                                        .then(|| (entry, offered_input - wanted_output))
                                 })
                                 .collect::<Vec<_>>();
 
-                            matching_orders.sort_by(|a, b| {
-                                let profit_a = a.input_amount - order_data.output_amount;
-                                let profit_b = b.input_amount - order_data.output_amount;
-                                profit_b.cmp(&profit_a)
-                            });
+                            matching_orders
+                                .sort_by(|(_, profit_a), (_, profit_b)| profit_b.cmp(profit_a));
 
-                            let highest_profit_pair = matching_orders.first().cloned();
+                            let highest_profit_pair = matching_orders.first().copied();
 
-                            if let Some(highest_profit_pair) = highest_profit_pair {
+                            if let Some((highest_profit_pair, profit)) = highest_profit_pair {
                                 info!("Arbitrage opportunity found! Sending orders for hashes {} and {}, generating payloads.", highest_profit_pair.order_hash, order_data.order_hash);
+                                info!(
+                                    "expected profit: {}",
+                                    self.format_amount(
+                                        order_data.output_token,
+                                        order_data.chain_id as u64,
+                                        profit
+                                    )
+                                );
 
                                 // Generate orders
                                 let orders = vec![highest_profit_pair.clone(), order_data.clone()];
                                 info!("vector of the highest profit: {:?}", orders);
-                                self.generate_take_orders(orders).await
+                                let orders = self.check_orders_onchain(orders).await;
+                                if orders.len() < 2 {
+                                    return vec![];
+                                }
+                                self.generate_match(orders, now)
                             } else {
                                 vec![]
                             }
@@ -148,6 +206,10 @@ impl Strategy<Event, Action> for SimpleArb {
                             vec![]
                         }
                     }
+                    AoriEvent::Unknown { event_type, .. } => {
+                        info!("Ignoring unknown aori event type {}", event_type);
+                        vec![]
+                    }
                     // on order cancelled, remove from orderbook hashmap
                     _ => vec![], // here handle other aori events
                 }
@@ -157,33 +219,148 @@ impl Strategy<Event, Action> for SimpleArb {
 }
 
 impl SimpleArb {
-    pub async fn generate_take_orders(&self, orders: Vec<OrderCreatedData>) -> Vec<Action> {
-        info!("Generating take orders for: {:?}", orders);
-        let mut actions = Vec::new();
-
-        for order in orders {
-            info!("Processing order: {:?}", order);
+    /// Checks `orders` against Seaport before they are matched. Each gets its offerer's
+    /// counter filled in, and orders that can't fill as listed are dropped, here and from
+    /// the stored orderbook: those whose hash doesn't match at the current counter, since
+    /// the offerer has bumped it, and those cancelled or (partly) filled on-chain. Orders
+    /// outside their time window, signed at a counter the offerer has since bumped, or whose
+    /// offerer lacks the balance or allowance to pay, are skipped. Those checks run in one
+    /// multicall per chain, which also reads every offerer's counter afresh.
+    pub async fn check_orders_onchain(
+        &mut self,
+        orders: Vec<OrderCreatedData>,
+    ) -> Vec<OrderCreatedData> {
+        let Some(node) = &self.node else {
+            return orders;
+        };
+        let mut candidates = Vec::with_capacity(orders.len());
+        for mut order in orders {
+            let chain = match self.chains.chain(order.chain_id as u64) {
+                Ok(chain) => chain,
+                Err(err) => {
+                    info!("skipping order {}: {}", order.order_hash, err);
+                    continue;
+                }
+            };
+            let offerer = order.order.parameters.offerer;
+            let counter = match self.counters.counter(node, chain, offerer).await {
+                Ok(counter) => counter,
+                Err(err) => {
+                    info!("skipping order {}: counter: {}", order.order_hash, err);
+                    continue;
+                }
+            };
+            if !order.verify_order_hash(counter) {
+                info!(
+                    "dropping order {}: not signed at counter {} of {}",
+                    order.order_hash, counter, offerer
+                );
+                self.orderbook_entries
+                    .retain(|entry| entry.order_hash != order.order_hash);
+                continue;
+            }
+            match cross_check_order_status(node, chain, order.order_hash, order.active).await {
+                Ok(None) => {}
+                Ok(Some(mismatch)) => {
+                    info!("dropping order {}: {}", order.order_hash, mismatch);
+                    self.orderbook_entries
+                        .retain(|entry| entry.order_hash != order.order_hash);
+                    continue;
+                }
+                Err(err) => {
+                    info!("skipping order {}: status: {}", order.order_hash, err);
+                    continue;
+                }
+            }
 
-            info!(
-                "Creating payload using these order params {:?}",
-                order.order.parameters.clone().to_order_components()
-            );
-            let payload = create_take_order_payload(
-                &self.last_id,
-                &self.wallet,
-                order.order.parameters.to_order_components(),
-                &order.order_hash,
-                "0",
-                &self.api_key,
-            );
+            order.order.parameters.counter = Some(counter);
+            match OrderComponents::try_from(order.order.parameters.clone()) {
+                Ok(components) => candidates.push((order, components)),
+                Err(err) => info!("skipping order {}: {}", order.order_hash, err),
+            }
+        }
 
-            info!("Payload created: {:?}", payload);
+        // one multicall per chain for every order left; an offerer short of funds or
+        // allowance may top them up, so those orders stay stored
+        let mut checked = Vec::with_capacity(candidates.len());
+        while let Some((first, _)) = candidates.first() {
+            let chain_id = first.chain_id;
+            let (batch, rest): (Vec<_>, Vec<_>) = candidates
+                .into_iter()
+                .partition(|(order, _)| order.chain_id == chain_id);
+            candidates = rest;
+            let Ok(chain) = self.chains.chain(chain_id as u64) else {
+                continue;
+            };
+            let fills: Vec<(OrderComponents, FillAmount)> = batch
+                .iter()
+                .map(|(_, components)| (components.clone(), FillAmount::Full))
+                .collect();
+            let verdicts =
+                match check_fillability(Arc::new(node.clone()), chain, &fills, &mut self.counters)
+                    .await
+                {
+                    Ok(verdicts) => verdicts,
+                    Err(err) => {
+                        info!("skipping {} orders: fillability: {}", batch.len(), err);
+                        continue;
+                    }
+                };
+            for ((order, _), verdict) in batch.into_iter().zip(verdicts) {
+                if verdict.is_fillable() {
+                    checked.push(order);
+                    continue;
+                }
+                for problem in &verdict.problems {
+                    info!("skipping order {}: {}", order.order_hash, problem);
+                }
+            }
+        }
+        checked
+    }
 
-            let action = Action::SendAoriPayload(payload.unwrap());
+    /// Settles `orders` against each other in one `matchOrders` call, so the arbitrage goes
+    /// through whole or not at all. Auction legs are priced at `now`, while Seaport prices
+    /// them at the block the match lands in; a match that no longer balances by then reverts.
+    pub fn generate_match(&self, orders: Vec<OrderCreatedData>, now: u64) -> Vec<Action> {
+        info!("Generating match for: {:?}", orders);
+        let mut legs = Vec::with_capacity(orders.len());
+        for order in orders {
+            if let Err(err) = self.chains.chain(order.chain_id as u64) {
+                info!("skipping match on order {}: {}", order.order_hash, err);
+                return vec![];
+            }
+            let signature = match hex::decode(&order.order.signature) {
+                Ok(signature) => signature,
+                Err(err) => {
+                    info!(
+                        "skipping match on order {}: signature: {}",
+                        order.order_hash, err
+                    );
+                    return vec![];
+                }
+            };
+            // orders without a counter fail here; `check_orders_onchain` fills it in
+            match OrderComponents::try_from(order.order.parameters) {
+                Ok(order_components) => legs.push((order_components, signature)),
+                Err(err) => {
+                    info!("skipping match on order {}: {}", order.order_hash, err);
+                    return vec![];
+                }
+            }
+        }
 
-            actions.push(action);
+        match OrderMatch::new(legs, now) {
+            Ok(order_match) => {
+                for item in &order_match.surplus {
+                    info!("match surplus: {} of {}", item.amount, item.token);
+                }
+                vec![Action::MatchOrders(order_match)]
+            }
+            Err(err) => {
+                info!("orders don't match: {}", err);
+                vec![]
+            }
         }
-        info!("Payloads generated: {:?}", actions);
-        actions
     }
 }
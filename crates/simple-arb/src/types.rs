@@ -1,15 +1,21 @@
 use aori_types::events::AoriEvent;
+use aori_types::matching::OrderMatch;
+use ethers::types::Log;
 use serde_json::Value;
 
 /// Core Event enum for the current strategy.
 #[derive(Debug, Clone)]
 pub enum Event {
     AoriTransaction(AoriEvent),
+    /// A log Seaport emitted on the chain with this id, e.g. a `CounterIncremented`.
+    SeaportLog(u64, Box<Log>),
 }
 
 /// Core Action enum for the current strategy.
 #[derive(Debug, Clone)]
 pub enum Action {
     SendAoriPayload(Value),
+    /// Settle orders against each other on-chain in one transaction.
+    MatchOrders(OrderMatch),
     // SubmitTx(SubmitTxToMempool),
 }
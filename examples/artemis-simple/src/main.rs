@@ -2,10 +2,12 @@ use aori_artemis::collector::AoriCollector;
 use aori_requests::aori_provider::AoriProvider;
 
 use aori_artemis::engine::Engine;
-use aori_artemis::executor::AoriExecutor;
+use aori_artemis::executor::{AoriExecutor, MatchExecutor};
 use aori_artemis::types::ExecutorMap;
 
+use artemis_core::collectors::log_collector::LogCollector;
 use artemis_core::types::CollectorMap;
+use ethers::types::{Filter, H160};
 use simple_arb::{
     strategy::SimpleArb,
     types::{Action, Event},
@@ -34,9 +36,14 @@ async fn main() -> anyhow::Result<()> {
     let provider2 = AoriProvider::new_from_env()
         .await
         .expect("failed to initialise aori provider");
-    let wallet_is = provider.wallet.clone().expect("msg");
-    let last_id_is = provider.last_id.clone();
-    let api_key_is = std::env::var("API_KEY").expect("API_KEY not found in environment");
+    let node = provider
+        .node
+        .clone()
+        .expect("NODE_URL not found in environment");
+    let chain = provider
+        .chain()
+        .expect("chain of NODE_URL not configured")
+        .clone();
 
     let provider = Arc::new(Mutex::new(provider));
     let provider2 = Arc::new(Mutex::new(provider2));
@@ -49,13 +56,33 @@ async fn main() -> anyhow::Result<()> {
 
     engine.add_collector(Box::new(collector));
 
+    // Seaport's counter bumps, so the strategy's cached counters don't go stale
+    let counter_bumps = Filter::new()
+        .address(H160::from_slice(chain.seaport_address.as_slice()))
+        .event("CounterIncremented(uint256,address)");
+    let collector = Box::new(LogCollector::new(Arc::new(node.clone()), counter_bumps));
+    let chain_id = chain.chain_id;
+    let collector = CollectorMap::new(collector, move |log| {
+        Event::SeaportLog(chain_id, Box::new(log))
+    });
+    engine.add_collector(Box::new(collector));
+
     // Set up strategy.
-    let strategy = SimpleArb::new(wallet_is, last_id_is, api_key_is);
+    let strategy = SimpleArb::new().with_node(node);
     engine.add_strategy(Box::new(strategy));
 
-    let executor = Box::new(AoriExecutor::new(provider2));
+    let executor = Box::new(AoriExecutor::new(provider2.clone()));
     let executor = ExecutorMap::new(executor, |action| match action {
         Action::SendAoriPayload(payload) => Some(payload),
+        _ => None,
+    });
+    engine.add_executor(Box::new(executor));
+
+    // matches go out through the same connection as the other payloads
+    let executor = Box::new(MatchExecutor::new(provider2));
+    let executor = ExecutorMap::new(executor, |action| match action {
+        Action::MatchOrders(order_match) => Some(order_match),
+        _ => None,
     });
     engine.add_executor(Box::new(executor));
 